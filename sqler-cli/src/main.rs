@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use sqler::{format_sql, Config, LineBreakStyle, SqlDialect};
 use std::fs;
 use std::io::{self, Read};
@@ -15,7 +15,7 @@ struct Cli {
     output: Option<String>,
 
     /// Number of spaces for indentation
-    #[arg(short, long, default_value = "2")]
+    #[arg(long, default_value = "2")]
     indent: usize,
 
     /// Maximum line length
@@ -25,6 +25,29 @@ struct Cli {
     /// Check if input is properly formatted without modifying it
     #[arg(short, long)]
     check: bool,
+
+    /// SQL dialect, for its quoting and literal rules
+    #[arg(short, long, value_enum, default_value_t = Dialect::Generic)]
+    dialect: Dialect,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Dialect {
+    Generic,
+    Tsql,
+    Postgres,
+    Mysql,
+}
+
+impl From<Dialect> for SqlDialect {
+    fn from(dialect: Dialect) -> Self {
+        match dialect {
+            Dialect::Generic => SqlDialect::Generic,
+            Dialect::Tsql => SqlDialect::TSql,
+            Dialect::Postgres => SqlDialect::PostgreSql,
+            Dialect::Mysql => SqlDialect::MySql,
+        }
+    }
 }
 
 fn read_input(input: Option<String>) -> io::Result<String> {
@@ -48,6 +71,15 @@ fn write_output(output: Option<String>, content: &str) -> io::Result<()> {
     }
 }
 
+// 1-based line and column of a byte offset into the input
+fn line_and_column(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset.min(input.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+
+    (line, column)
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
@@ -59,7 +91,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         max_line_length: cli.max_length,
         align_columns: true,
         line_breaks: LineBreakStyle::Always,
-        dialect: SqlDialect::Generic,
+        dialect: cli.dialect.into(),
     };
 
     let input = read_input(cli.input)?;
@@ -78,7 +110,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         Err(e) => {
-            eprintln!("Error formatting SQL: {}", e);
+            match e.span() {
+                Some(span) => {
+                    let (line, column) = line_and_column(&input, span.start);
+                    eprintln!("Error formatting SQL at {}:{}: {}", line, column, e);
+                }
+                None => eprintln!("Error formatting SQL: {}", e),
+            }
             std::process::exit(1);
         }
     }
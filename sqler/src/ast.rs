@@ -15,23 +15,594 @@ impl From<Range<usize>> for Span {
     }
 }
 
+// a comment kept from the source text. comments are not part of the syntax
+// tree proper; the formatter places each one next to the nearest node by span.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub span: Span,
+    pub text: String,
+    pub kind: CommentKind,
+    // true when the comment starts its own line rather than trailing code
+    pub own_line: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommentKind {
+    Line,
+    Block,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum AST {
-    Select(SelectStatement),
-    // Insert(InsertStatement),
-    // Update(UpdateStatement),
-    // Delete(DeleteStatement),
+    Query(Box<Query>),
+    Insert(Box<InsertStatement>),
+    Update(Box<UpdateStatement>),
+    Delete(Box<DeleteStatement>),
+    Merge(Box<MergeStatement>),
+    CreateTable(Box<CreateTableStatement>),
+    AlterTable(Box<AlterTableStatement>),
+    Drop(Box<DropStatement>),
+    Truncate(Box<TruncateStatement>),
+}
+
+// INSERT INTO table [(columns)] [OUTPUT ...] source [upsert] [RETURNING ...]
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsertStatement {
+    pub span: Span,
+    pub table: ObjectName,
+    pub columns: Vec<Identifier>,
+    pub source: InsertSource,
+    pub upsert: Option<Upsert>,
+    pub returning: Option<Returning>,
+    pub comments: Vec<Comment>,
+}
+
+// UPDATE tables SET ... [OUTPUT ...] [FROM ...] [WHERE ...] [RETURNING ...]
+// mysql updates several tables at once with joins or commas in `tables`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateStatement {
+    pub span: Span,
+    pub tables: Vec<TableExpression>,
+    pub assignments: Vec<Assignment>,
+    pub from: Option<Vec<TableExpression>>,
+    pub where_clause: Option<WhereClause>,
+    pub returning: Option<Returning>,
+    pub comments: Vec<Comment>,
+}
+
+// DELETE [TOP (n)] [FROM] tables [OUTPUT ...] [FROM ...] [USING ...] [WHERE ...]
+// [RETURNING ...]. t-sql and mysql name the rows to delete in `tables` and
+// join them in `from`, postgres joins them in `using`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteStatement {
+    pub span: Span,
+    pub top: Option<Expression>,
+    pub tables: Vec<TableExpression>,
+    pub from: Option<Vec<TableExpression>>,
+    pub using: Option<Vec<TableExpression>>,
+    pub where_clause: Option<WhereClause>,
+    pub returning: Option<Returning>,
+    pub comments: Vec<Comment>,
+}
+
+// MERGE [INTO] target USING source ON condition WHEN ... [OUTPUT ...]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeStatement {
+    pub span: Span,
+    pub target: TableReference,
+    pub source: TableExpression,
+    pub on: Expression,
+    pub clauses: Vec<MergeClause>,
+    pub returning: Option<Returning>,
+    pub comments: Vec<Comment>,
+}
+
+// WHEN [NOT] MATCHED [BY SOURCE | BY TARGET] [AND condition] THEN action
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeClause {
+    pub span: Span,
+    pub kind: MergeMatch,
+    pub condition: Option<Expression>,
+    pub action: MergeAction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeMatch {
+    Matched,
+    NotMatched,
+    NotMatchedByTarget, // same as NOT MATCHED, kept as written
+    NotMatchedBySource,
+}
+
+impl std::fmt::Display for MergeMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeMatch::Matched => write!(f, "MATCHED"),
+            MergeMatch::NotMatched => write!(f, "NOT MATCHED"),
+            MergeMatch::NotMatchedByTarget => write!(f, "NOT MATCHED BY TARGET"),
+            MergeMatch::NotMatchedBySource => write!(f, "NOT MATCHED BY SOURCE"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeAction {
+    Update(Vec<Assignment>),
+    Delete,
+    // a single VALUES row or DEFAULT VALUES
+    Insert {
+        columns: Vec<Identifier>,
+        source: InsertSource,
+    },
+    DoNothing,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InsertSource {
+    // VALUES (...), (...), one entry per row
+    Values(Vec<Vec<Expression>>),
+    Query(Box<Query>),
+    DefaultValues,
+}
+
+// what to do when an inserted row collides with an existing one
+#[derive(Debug, Clone, PartialEq)]
+pub enum Upsert {
+    // postgres ON CONFLICT [target] DO {NOTHING | UPDATE SET ... [WHERE ...]}
+    OnConflict {
+        span: Span,
+        target: Option<ConflictTarget>,
+        action: ConflictAction,
+    },
+    // mysql ON DUPLICATE KEY UPDATE ...
+    OnDuplicateKeyUpdate {
+        span: Span,
+        assignments: Vec<Assignment>,
+    },
+}
+
+impl Upsert {
+    pub fn span(&self) -> Span {
+        match self {
+            Upsert::OnConflict { span, .. } => span.clone(),
+            Upsert::OnDuplicateKeyUpdate { span, .. } => span.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictTarget {
+    Columns(Vec<Identifier>),
+    Constraint(Identifier), // ON CONSTRAINT name
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictAction {
+    DoNothing,
+    DoUpdate {
+        assignments: Vec<Assignment>,
+        where_clause: Option<Expression>,
+    },
+}
+
+// `column = value` in a SET list
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub span: Span,
+    pub column: ObjectName,
+    pub operator: AssignmentOperator,
+    pub value: Expression,
+}
+
+// `=`, or one of the t-sql compound assignments: += -= *= /= %= &= |= ^=
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssignmentOperator {
+    Equals,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+}
+
+impl std::fmt::Display for AssignmentOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssignmentOperator::Equals => write!(f, "="),
+            AssignmentOperator::Plus => write!(f, "+="),
+            AssignmentOperator::Minus => write!(f, "-="),
+            AssignmentOperator::Multiply => write!(f, "*="),
+            AssignmentOperator::Divide => write!(f, "/="),
+            AssignmentOperator::Modulo => write!(f, "%="),
+            AssignmentOperator::BitwiseAnd => write!(f, "&="),
+            AssignmentOperator::BitwiseOr => write!(f, "|="),
+            AssignmentOperator::BitwiseXor => write!(f, "^="),
+        }
+    }
+}
+
+// rows handed back by a data-changing statement: postgres RETURNING goes at
+// the end, t-sql OUTPUT comes earlier in the statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Returning {
+    pub span: Span,
+    pub kind: ReturningKind,
+    pub items: Vec<SelectItem>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReturningKind {
+    Returning,
+    Output,
+}
+
+impl std::fmt::Display for ReturningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturningKind::Returning => write!(f, "RETURNING"),
+            ReturningKind::Output => write!(f, "OUTPUT"),
+        }
+    }
+}
+
+// CREATE [TEMPORARY] TABLE [IF NOT EXISTS] name (columns and constraints)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateTableStatement {
+    pub span: Span,
+    pub temporary: bool,
+    pub if_not_exists: bool,
+    pub name: ObjectName,
+    pub elements: Vec<TableElement>,
+    pub comments: Vec<Comment>,
+}
+
+// columns and table constraints, in the order they were written
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableElement {
+    Column(ColumnDefinition),
+    Constraint(TableConstraint),
+}
+
+impl TableElement {
+    pub fn span(&self) -> Span {
+        match self {
+            TableElement::Column(column) => column.span.clone(),
+            TableElement::Constraint(constraint) => constraint.span.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDefinition {
+    pub span: Span,
+    pub name: Identifier,
+    pub data_type: DataType,
+    pub constraints: Vec<ColumnConstraint>,
+}
+
+// [CONSTRAINT name] constraint, following a column's type
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnConstraint {
+    pub span: Span,
+    pub name: Option<Identifier>,
+    pub kind: ColumnConstraintKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnConstraintKind {
+    NotNull,
+    Null,
+    Default(Expression),
+    PrimaryKey,
+    Unique,
+    References(ForeignKeyReference),
+    Check(Expression),
+    Collate(Identifier),
+    // t-sql IDENTITY[(seed, increment)]
+    Identity(Option<(Expression, Expression)>),
+    AutoIncrement,
+    // GENERATED {ALWAYS | BY DEFAULT} AS IDENTITY
+    GeneratedIdentity { always: bool },
+    // GENERATED ALWAYS AS (expr) [STORED]
+    GeneratedAs { expr: Expression, stored: bool },
+}
+
+// [CONSTRAINT name] constraint, as its own entry in the column list
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableConstraint {
+    pub span: Span,
+    pub name: Option<Identifier>,
+    pub kind: TableConstraintKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableConstraintKind {
+    PrimaryKey(Vec<Identifier>),
+    Unique(Vec<Identifier>),
+    ForeignKey {
+        columns: Vec<Identifier>,
+        references: ForeignKeyReference,
+    },
+    Check(Expression),
+}
+
+// REFERENCES table [(columns)] [ON DELETE action] [ON UPDATE action]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignKeyReference {
+    pub table: ObjectName,
+    pub columns: Vec<Identifier>,
+    pub on_delete: Option<ReferentialAction>,
+    pub on_update: Option<ReferentialAction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReferentialAction {
+    Cascade,
+    Restrict,
+    NoAction,
+    SetNull,
+    SetDefault,
+}
+
+impl std::fmt::Display for ReferentialAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferentialAction::Cascade => write!(f, "CASCADE"),
+            ReferentialAction::Restrict => write!(f, "RESTRICT"),
+            ReferentialAction::NoAction => write!(f, "NO ACTION"),
+            ReferentialAction::SetNull => write!(f, "SET NULL"),
+            ReferentialAction::SetDefault => write!(f, "SET DEFAULT"),
+        }
+    }
+}
+
+// ALTER TABLE [IF EXISTS] name action [, ...]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlterTableStatement {
+    pub span: Span,
+    pub if_exists: bool,
+    pub name: ObjectName,
+    pub actions: Vec<AlterTableAction>,
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlterTableAction {
+    pub span: Span,
+    pub kind: AlterTableActionKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlterTableActionKind {
+    // ADD [COLUMN] [IF NOT EXISTS] definition. t-sql has no COLUMN keyword
+    // here, so whether it was written is kept
+    AddColumn {
+        column_keyword: bool,
+        if_not_exists: bool,
+        column: ColumnDefinition,
+    },
+    DropColumn {
+        if_exists: bool,
+        name: Identifier,
+        behavior: Option<DropBehavior>,
+    },
+    AlterColumn {
+        name: Identifier,
+        change: AlterColumnChange,
+    },
+    AddConstraint(TableConstraint),
+    DropConstraint {
+        if_exists: bool,
+        name: Identifier,
+        behavior: Option<DropBehavior>,
+    },
+    RenameColumn {
+        old: Identifier,
+        new: Identifier,
+    },
+    RenameTable(ObjectName),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlterColumnChange {
+    SetDataType(DataType), // [SET DATA] TYPE t
+    SetDefault(Expression),
+    DropDefault,
+    SetNotNull,
+    DropNotNull,
+}
+
+// DROP {TABLE | VIEW | INDEX | SCHEMA} [IF EXISTS] names [ON table]
+// [CASCADE | RESTRICT]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropStatement {
+    pub span: Span,
+    pub object_type: ObjectType,
+    pub if_exists: bool,
+    pub names: Vec<ObjectName>,
+    pub table: Option<ObjectName>, // the table a t-sql or mysql index belongs to
+    pub behavior: Option<DropBehavior>,
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectType {
+    Table,
+    View,
+    Index,
+    Schema,
+}
+
+impl std::fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObjectType::Table => write!(f, "TABLE"),
+            ObjectType::View => write!(f, "VIEW"),
+            ObjectType::Index => write!(f, "INDEX"),
+            ObjectType::Schema => write!(f, "SCHEMA"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DropBehavior {
+    Cascade,
+    Restrict,
+}
+
+impl std::fmt::Display for DropBehavior {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DropBehavior::Cascade => write!(f, "CASCADE"),
+            DropBehavior::Restrict => write!(f, "RESTRICT"),
+        }
+    }
+}
+
+// TRUNCATE [TABLE] names [RESTART IDENTITY] [CASCADE | RESTRICT]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TruncateStatement {
+    pub span: Span,
+    pub tables: Vec<ObjectName>,
+    pub restart_identity: bool,
+    pub behavior: Option<DropBehavior>,
+    pub comments: Vec<Comment>,
+}
+
+// a full query. ORDER BY and the row limits apply to the whole body, which
+// may be several SELECTs combined by set operators
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub span: Span,
+    pub with: Option<With>,
+    pub body: SetExpression,
+    pub order_by: Option<Vec<OrderByItem>>,
+    pub limit: Option<Expression>,
+    pub offset: Option<Offset>,
+    pub fetch: Option<Fetch>,
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetExpression {
+    Select(Box<SelectStatement>),
+    // a parenthesized query, which can carry its own ORDER BY and limits
+    Query(Box<Query>),
+    Operation {
+        span: Span,
+        left: Box<SetExpression>,
+        op: SetOperator,
+        quantifier: Option<Quantifier>,
+        right: Box<SetExpression>,
+    },
+}
+
+impl SetExpression {
+    pub fn span(&self) -> Span {
+        match self {
+            SetExpression::Select(select) => select.span.clone(),
+            SetExpression::Query(query) => query.span.clone(),
+            SetExpression::Operation { span, .. } => span.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetOperator {
+    Union,
+    Intersect,
+    Except,
+}
+
+impl SetOperator {
+    // INTERSECT binds tighter than UNION and EXCEPT
+    pub fn precedence(&self) -> u8 {
+        match self {
+            SetOperator::Union | SetOperator::Except => 1,
+            SetOperator::Intersect => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for SetOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetOperator::Union => write!(f, "UNION"),
+            SetOperator::Intersect => write!(f, "INTERSECT"),
+            SetOperator::Except => write!(f, "EXCEPT"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Quantifier {
+    All,
+    Distinct,
+}
+
+impl std::fmt::Display for Quantifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Quantifier::All => write!(f, "ALL"),
+            Quantifier::Distinct => write!(f, "DISTINCT"),
+        }
+    }
+}
+
+// SELECT ALL, SELECT DISTINCT or postgres SELECT DISTINCT ON (...)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectQuantifier {
+    All,
+    Distinct,
+    DistinctOn(Vec<Expression>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct SelectStatement {
     pub span: Span,
+    pub quantifier: Option<SelectQuantifier>,
     pub columns: Vec<SelectItem>,
-    pub from: TableReference,
+    pub from: Option<Vec<TableExpression>>, // comma separated items
     pub where_clause: Option<WhereClause>,
     pub group_by: Option<Vec<Expression>>,
     pub having: Option<Expression>,
-    // pub order_by: Option<Vec<OrderByItem>>,
+    pub window: Option<Vec<NamedWindow>>,
+}
+
+// WITH [RECURSIVE] cte [, ...]
+#[derive(Debug, Clone, PartialEq)]
+pub struct With {
+    pub span: Span,
+    pub recursive: bool,
+    pub ctes: Vec<Cte>,
+}
+
+// a common table expression, `name [(columns)] AS [[NOT] MATERIALIZED] (query)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cte {
+    pub span: Span,
+    pub name: Identifier,
+    pub columns: Vec<Identifier>,
+    pub materialized: Option<Materialization>,
+    pub query: Box<Query>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Materialization {
+    Materialized,
+    NotMaterialized,
+}
+
+impl std::fmt::Display for Materialization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Materialization::Materialized => write!(f, "MATERIALIZED"),
+            Materialization::NotMaterialized => write!(f, "NOT MATERIALIZED"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -41,26 +612,40 @@ pub enum SelectItem {
     },
     QualifiedWildcard {
         span: Span,
-        qualifier: String,
+        qualifier: ObjectName,
     },
     Expression {
         span: Span,
         expr: Expression,
-        alias: Option<String>,
+        alias: Option<Identifier>,
     },
 }
 
+impl SelectItem {
+    pub fn span(&self) -> Span {
+        match self {
+            SelectItem::Wildcard { span } => span.clone(),
+            SelectItem::QualifiedWildcard { span, .. } => span.clone(),
+            SelectItem::Expression { span, .. } => span.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
+    // a column, possibly qualified, e.g. `id`, `o.id` or `db.dbo.orders.id`
     Column {
         span: Span,
-        name: String,
-        table: Option<String>,
+        name: ObjectName,
     },
     Literal {
         span: Span,
         value: LiteralValue,
     },
+    // DEFAULT as a VALUES entry or the value in a SET list
+    Default {
+        span: Span,
+    },
     Asterisk {
         span: Span,
     },
@@ -70,10 +655,104 @@ pub enum Expression {
         op: Operator,
         right: Box<Expression>,
     },
+    // name([ALL | DISTINCT] args [ORDER BY ...]) [WITHIN GROUP (ORDER BY ...)]
+    // [FILTER (WHERE ...)] [OVER ...]. COUNT(*) has a single `Asterisk` argument
     Function {
         span: Span,
-        name: String,
+        name: ObjectName,
         args: Vec<Expression>,
+        quantifier: Option<Quantifier>,
+        order_by: Vec<OrderByItem>,
+        within_group: Vec<OrderByItem>,
+        filter: Option<Box<Expression>>,
+        over: Option<Window>,
+    },
+    UnaryOperation {
+        span: Span,
+        op: UnaryOperator,
+        expr: Box<Expression>,
+    },
+    // a parenthesized expression, kept so the parentheses survive formatting
+    Nested {
+        span: Span,
+        expr: Box<Expression>,
+    },
+    // a scalar `(SELECT ...)`
+    Subquery {
+        span: Span,
+        query: Box<Query>,
+    },
+    Exists {
+        span: Span,
+        query: Box<Query>,
+    },
+    // expr [NOT] IN (SELECT ...)
+    InSubquery {
+        span: Span,
+        expr: Box<Expression>,
+        query: Box<Query>,
+        negated: bool,
+    },
+    // expr [NOT] IN (a, b, ...)
+    InList {
+        span: Span,
+        expr: Box<Expression>,
+        list: Vec<Expression>,
+        negated: bool,
+    },
+    // expr [NOT] BETWEEN low AND high
+    Between {
+        span: Span,
+        expr: Box<Expression>,
+        low: Box<Expression>,
+        high: Box<Expression>,
+        negated: bool,
+    },
+    // expr [NOT] {LIKE | ILIKE | SIMILAR TO} pattern [ESCAPE escape]
+    Like {
+        span: Span,
+        expr: Box<Expression>,
+        kind: LikeKind,
+        pattern: Box<Expression>,
+        escape: Option<Box<Expression>>,
+        negated: bool,
+    },
+    // expr IS [NOT] {NULL | TRUE | FALSE}
+    Is {
+        span: Span,
+        expr: Box<Expression>,
+        value: IsValue,
+        negated: bool,
+    },
+    // left IS [NOT] DISTINCT FROM right
+    IsDistinctFrom {
+        span: Span,
+        left: Box<Expression>,
+        right: Box<Expression>,
+        negated: bool,
+    },
+    // CAST(expr AS type), TRY_CAST(expr AS type), expr::type and t-sql
+    // CONVERT(type, expr[, style]) / TRY_CONVERT(...)
+    Cast {
+        span: Span,
+        kind: CastKind,
+        expr: Box<Expression>,
+        data_type: DataType,
+        style: Option<Box<Expression>>,
+    },
+    // an array element, expr[index]
+    Subscript {
+        span: Span,
+        expr: Box<Expression>,
+        index: Box<Expression>,
+    },
+    // CASE [operand] WHEN ... THEN ... [ELSE ...] END. with an operand each
+    // WHEN holds a value to compare against, otherwise a condition
+    Case {
+        span: Span,
+        operand: Option<Box<Expression>>,
+        conditions: Vec<WhenClause>,
+        else_result: Option<Box<Expression>>,
     },
 }
 
@@ -82,27 +761,353 @@ impl Expression {
         match self {
             Expression::Column { span, .. } => span.clone(),
             Expression::Literal { span, .. } => span.clone(),
+            Expression::Default { span } => span.clone(),
             Expression::BinaryOperation { span, .. } => span.clone(),
             Expression::Function { span, .. } => span.clone(),
             Expression::Asterisk { span } => span.clone(),
+            Expression::UnaryOperation { span, .. } => span.clone(),
+            Expression::Nested { span, .. } => span.clone(),
+            Expression::Subquery { span, .. } => span.clone(),
+            Expression::Exists { span, .. } => span.clone(),
+            Expression::InSubquery { span, .. } => span.clone(),
+            Expression::InList { span, .. } => span.clone(),
+            Expression::Between { span, .. } => span.clone(),
+            Expression::Like { span, .. } => span.clone(),
+            Expression::Is { span, .. } => span.clone(),
+            Expression::IsDistinctFrom { span, .. } => span.clone(),
+            Expression::Case { span, .. } => span.clone(),
+            Expression::Cast { span, .. } => span.clone(),
+            Expression::Subscript { span, .. } => span.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LikeKind {
+    Like,
+    ILike,
+    SimilarTo,
+}
+
+impl std::fmt::Display for LikeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LikeKind::Like => write!(f, "LIKE"),
+            LikeKind::ILike => write!(f, "ILIKE"),
+            LikeKind::SimilarTo => write!(f, "SIMILAR TO"),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IsValue {
+    Null,
+    True,
+    False,
+}
+
+impl std::fmt::Display for IsValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IsValue::Null => write!(f, "NULL"),
+            IsValue::True => write!(f, "TRUE"),
+            IsValue::False => write!(f, "FALSE"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CastKind {
+    Cast,
+    TryCast,
+    DoubleColon,
+    Convert,
+    TryConvert,
+}
+
+// a type as written in a cast or column definition, e.g. `int`,
+// `DECIMAL(10, 2)`, `varchar(max)`, `text[]` or a user-defined `app.money`.
+// multi-word names like `double precision` are kept as a single name part
 #[derive(Debug, Clone, PartialEq)]
-pub enum LiteralValue {
-    String(String),
+pub struct DataType {
+    pub span: Span,
+    pub name: ObjectName,
+    pub arguments: Vec<TypeArgument>,
+    pub time_zone: Option<TimeZone>,
+    // one entry per `[]`, holding the bound if there is one
+    pub array_dimensions: Vec<Option<String>>,
+}
+
+// a length, precision or scale
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeArgument {
     Number(String),
+    Max,
+}
+
+impl std::fmt::Display for TypeArgument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeArgument::Number(n) => write!(f, "{}", n),
+            TypeArgument::Max => write!(f, "MAX"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeZone {
+    With,
+    Without,
+}
+
+impl std::fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeZone::With => write!(f, "WITH TIME ZONE"),
+            TimeZone::Without => write!(f, "WITHOUT TIME ZONE"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhenClause {
+    pub span: Span,
+    pub condition: Expression,
+    pub result: Expression,
+}
+
+// numeric literals keep their original spelling so formatting never changes them
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiteralValue {
+    String {
+        value: String, // unescaped contents
+        raw: String,   // as written, including any prefix and the quotes
+        kind: StringKind,
+    },
+    Number(String), // 42, 3.14, .5, 1e10
+    Hex(String),    // 0x1F, X'1F'
+    Binary(String), // 0b101, B'101'
     Boolean(bool),
     Null,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringKind {
+    Regular,  // 'text'
+    National, // N'text'
+    Escape,   // E'text', with backslash escapes
+}
+
+// a name as written in the source. `value` is unquoted and unescaped; the
+// quote style is kept so the name can be written back out the same way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identifier {
+    pub value: String,
+    pub quote_style: QuoteStyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteStyle {
+    None,
+    DoubleQuote, // "name"
+    Backtick,    // `name`
+    Bracket,     // [name]
+}
+
+impl Identifier {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            quote_style: QuoteStyle::None,
+        }
+    }
+
+    pub fn quoted(value: impl Into<String>, quote_style: QuoteStyle) -> Self {
+        Self {
+            value: value.into(),
+            quote_style,
+        }
+    }
+}
+
+// identifiers compare against plain strings by value, ignoring quoting
+impl PartialEq<str> for Identifier {
+    fn eq(&self, other: &str) -> bool {
+        self.value == other
+    }
+}
+
+impl PartialEq<&str> for Identifier {
+    fn eq(&self, other: &&str) -> bool {
+        self.value == *other
+    }
+}
+
+impl std::fmt::Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // re-escape the closing quote by doubling it
+        match self.quote_style {
+            QuoteStyle::None => write!(f, "{}", self.value),
+            QuoteStyle::DoubleQuote => write!(f, "\"{}\"", self.value.replace('"', "\"\"")),
+            QuoteStyle::Backtick => write!(f, "`{}`", self.value.replace('`', "``")),
+            QuoteStyle::Bracket => write!(f, "[{}]", self.value.replace(']', "]]")),
+        }
+    }
+}
+
+// what follows OVER: a named window, or a window specification in parentheses
+#[derive(Debug, Clone, PartialEq)]
+pub enum Window {
+    Named(Identifier),
+    Specification(Box<WindowSpec>),
+}
+
+// ([base window] [PARTITION BY ...] [ORDER BY ...] [frame])
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSpec {
+    pub span: Span,
+    pub base: Option<Identifier>,
+    pub partition_by: Vec<Expression>,
+    pub order_by: Vec<OrderByItem>,
+    pub frame: Option<WindowFrame>,
+}
+
+// {ROWS | RANGE | GROUPS} {start | BETWEEN start AND end}
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowFrame {
+    pub units: FrameUnits,
+    pub start: FrameBound,
+    pub end: Option<FrameBound>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameUnits {
+    Rows,
+    Range,
+    Groups,
+}
+
+impl std::fmt::Display for FrameUnits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameUnits::Rows => write!(f, "ROWS"),
+            FrameUnits::Range => write!(f, "RANGE"),
+            FrameUnits::Groups => write!(f, "GROUPS"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(Box<Expression>),
+    CurrentRow,
+    Following(Box<Expression>),
+    UnboundedFollowing,
+}
+
+// `name AS (spec)` in a WINDOW clause
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedWindow {
+    pub span: Span,
+    pub name: Identifier,
+    pub spec: WindowSpec,
+}
+
+// a possibly qualified name, e.g. `dbo.fn_x`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectName(pub Vec<Identifier>);
+
+impl std::fmt::Display for ObjectName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, part) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", part)?;
+        }
+        Ok(())
+    }
+}
+
+// a table, possibly qualified, e.g. `users`, `dbo.users`,
+// `project.dataset.table` or `server.db.dbo.users`
 #[derive(Debug, Clone, PartialEq)]
 pub struct TableReference {
     pub span: Span,
-    pub name: String,
-    pub alias: Option<String>,
-    pub schema: Option<String>,
+    pub name: ObjectName,
+    pub alias: Option<Identifier>,
+}
+
+// an item in the FROM clause: a single table, or a tree of joined tables
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableExpression {
+    Table(TableReference),
+    Join(Box<Join>),
+    // a subquery in FROM, `(SELECT ...) AS alias`
+    Derived {
+        span: Span,
+        query: Box<Query>,
+        alias: Option<Identifier>,
+    },
+    // a join tree in parentheses, `(a JOIN b ON ...)`
+    Nested {
+        span: Span,
+        expr: Box<TableExpression>,
+    },
+}
+
+impl TableExpression {
+    pub fn span(&self) -> Span {
+        match self {
+            TableExpression::Table(table) => table.span.clone(),
+            TableExpression::Join(join) => join.span.clone(),
+            TableExpression::Derived { span, .. } => span.clone(),
+            TableExpression::Nested { span, .. } => span.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    pub span: Span,
+    pub left: TableExpression,
+    pub right: TableExpression,
+    pub kind: JoinKind,
+    pub natural: bool,
+    pub constraint: Option<JoinConstraint>,
+}
+
+// the join keywords, down to whether OUTER was spelled out
+#[derive(Debug, Clone, PartialEq)]
+pub enum JoinKind {
+    Join, // a bare JOIN, which is an inner join
+    Inner,
+    Left { outer: bool },
+    Right { outer: bool },
+    Full { outer: bool },
+    Cross,
+}
+
+impl std::fmt::Display for JoinKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let outer = |outer: &bool| if *outer { " OUTER" } else { "" };
+
+        match self {
+            JoinKind::Join => write!(f, "JOIN"),
+            JoinKind::Inner => write!(f, "INNER JOIN"),
+            JoinKind::Left { outer: o } => write!(f, "LEFT{} JOIN", outer(o)),
+            JoinKind::Right { outer: o } => write!(f, "RIGHT{} JOIN", outer(o)),
+            JoinKind::Full { outer: o } => write!(f, "FULL{} JOIN", outer(o)),
+            JoinKind::Cross => write!(f, "CROSS JOIN"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JoinConstraint {
+    On(Expression),
+    Using(Vec<Identifier>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -111,37 +1116,199 @@ pub struct WhereClause {
     pub condition: Expression,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderByItem {
+    pub span: Span,
+    pub expr: Expression,
+    pub direction: Option<SortDirection>,
+    pub nulls: Option<NullsOrder>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+impl std::fmt::Display for SortDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortDirection::Asc => write!(f, "ASC"),
+            SortDirection::Desc => write!(f, "DESC"),
+        }
+    }
+}
+
+impl std::fmt::Display for NullsOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NullsOrder::First => write!(f, "NULLS FIRST"),
+            NullsOrder::Last => write!(f, "NULLS LAST"),
+        }
+    }
+}
+
+// OFFSET n [ROW | ROWS]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Offset {
+    pub span: Span,
+    pub value: Expression,
+    pub rows: Option<RowsKeyword>,
+}
+
+// FETCH {FIRST | NEXT} [n] {ROW | ROWS} ONLY
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fetch {
+    pub span: Span,
+    pub next: bool,
+    pub quantity: Option<Expression>,
+    pub rows: RowsKeyword,
+}
+
+// ROW and ROWS mean the same thing, the spelling is kept as written
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowsKeyword {
+    Row,
+    Rows,
+}
+
+impl std::fmt::Display for RowsKeyword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RowsKeyword::Row => write!(f, "ROW"),
+            RowsKeyword::Rows => write!(f, "ROWS"),
+        }
+    }
+}
+
+// binary operators. `<>` and `!=` are kept apart so each is written back the way
+// it was spelled.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Operator {
     Equals,
     NotEquals,
+    BangEquals,
     LessThan,
     GreaterThan,
     LessEquals,
     GreaterEquals,
+    NotLessThan,
+    NotGreaterThan,
     And,
     Or,
     Plus,
     Minus,
     Multiply,
     Divide,
+    Modulo,
+    Concat,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
+    JsonGet,     // ->
+    JsonGetText, // ->>
+    Contains,    // @>
+    ContainedBy, // <@
+}
+
+impl Operator {
+    // how tightly the operator binds; higher binds tighter
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Operator::Or => 2,
+            Operator::And => 3,
+            // NOT sits at 4, between AND and the comparisons
+            Operator::Equals
+            | Operator::NotEquals
+            | Operator::BangEquals
+            | Operator::LessThan
+            | Operator::GreaterThan
+            | Operator::LessEquals
+            | Operator::GreaterEquals
+            | Operator::NotLessThan
+            | Operator::NotGreaterThan => 5,
+            Operator::Concat
+            | Operator::BitwiseAnd
+            | Operator::BitwiseOr
+            | Operator::BitwiseXor
+            | Operator::ShiftLeft
+            | Operator::ShiftRight
+            | Operator::JsonGet
+            | Operator::JsonGetText
+            | Operator::Contains
+            | Operator::ContainedBy => 6,
+            Operator::Plus | Operator::Minus => 7,
+            Operator::Multiply | Operator::Divide | Operator::Modulo => 8,
+            // unary +, - and ~ sit at 9, postgres `::` casts at 10
+        }
+    }
 }
 
 impl std::fmt::Display for Operator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Operator::Equals => write!(f, "="),
-            Operator::NotEquals => write!(f, "!="),
+            Operator::NotEquals => write!(f, "<>"),
+            Operator::BangEquals => write!(f, "!="),
             Operator::LessThan => write!(f, "<"),
             Operator::GreaterThan => write!(f, ">"),
             Operator::LessEquals => write!(f, "<="),
             Operator::GreaterEquals => write!(f, ">="),
+            Operator::NotLessThan => write!(f, "!<"),
+            Operator::NotGreaterThan => write!(f, "!>"),
             Operator::And => write!(f, "AND"),
             Operator::Or => write!(f, "OR"),
             Operator::Plus => write!(f, "+"),
             Operator::Minus => write!(f, "-"),
             Operator::Multiply => write!(f, "*"),
             Operator::Divide => write!(f, "/"),
+            Operator::Modulo => write!(f, "%"),
+            Operator::Concat => write!(f, "||"),
+            Operator::BitwiseAnd => write!(f, "&"),
+            Operator::BitwiseOr => write!(f, "|"),
+            Operator::BitwiseXor => write!(f, "^"),
+            Operator::ShiftLeft => write!(f, "<<"),
+            Operator::ShiftRight => write!(f, ">>"),
+            Operator::JsonGet => write!(f, "->"),
+            Operator::JsonGetText => write!(f, "->>"),
+            Operator::Contains => write!(f, "@>"),
+            Operator::ContainedBy => write!(f, "<@"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnaryOperator {
+    Not,
+    Plus,
+    Minus,
+    BitwiseNot,
+}
+
+impl UnaryOperator {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            UnaryOperator::Not => 4,
+            UnaryOperator::Plus | UnaryOperator::Minus | UnaryOperator::BitwiseNot => 9,
+        }
+    }
+}
+
+impl std::fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnaryOperator::Not => write!(f, "NOT"),
+            UnaryOperator::Plus => write!(f, "+"),
+            UnaryOperator::Minus => write!(f, "-"),
+            UnaryOperator::BitwiseNot => write!(f, "~"),
         }
     }
 }
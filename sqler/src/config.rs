@@ -30,8 +30,11 @@ pub enum LineBreakStyle {
     Inline,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum SqlDialect {
+    #[default]
     Generic,
     TSql,
+    PostgreSql,
+    MySql,
 }
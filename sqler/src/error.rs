@@ -16,4 +16,39 @@ pub enum ParseError {
 
     #[error("Invalid syntax: {0}")]
     InvalidSyntax(String),
+
+    #[error("Lexical error: {error}")]
+    Lexical { error: LexError, span: Range<usize> },
+}
+
+impl ParseError {
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            ParseError::UnexpectedToken { span, .. } => Some(span.clone()),
+            ParseError::Lexical { span, .. } => Some(span.clone()),
+            ParseError::UnexpectedEOF | ParseError::InvalidSyntax(_) => None,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Default)]
+pub enum LexError {
+    #[default]
+    #[error("unexpected character")]
+    UnexpectedCharacter,
+
+    #[error("unterminated string literal")]
+    UnterminatedString,
+
+    #[error("unterminated quoted identifier")]
+    UnterminatedIdentifier,
+
+    #[error("empty quoted identifier")]
+    EmptyIdentifier,
+
+    #[error("unterminated block comment")]
+    UnterminatedComment,
+
+    #[error("identifier quoting not supported by this dialect")]
+    UnsupportedQuoting,
 }
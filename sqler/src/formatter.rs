@@ -4,82 +4,998 @@ use crate::error::ParseError;
 use crate::parser::Parser;
 
 pub fn format_sql(sql: &str, config: &Config) -> Result<String, ParseError> {
-    let mut parser = Parser::new(sql);
-    let ast = parser.parse_select()?;
+    let mut parser = Parser::with_dialect(sql, config.dialect);
+    let statements = parser.parse_statements()?;
 
+    // refuse to format input the lexer couldn't fully account for rather
+    // than silently dropping any of it
+    if let Some(diagnostic) = parser.diagnostics().first() {
+        return Err(ParseError::Lexical {
+            error: diagnostic.error.clone(),
+            span: diagnostic.span.clone(),
+        });
+    }
+
+    // statements are separated by a blank line
     let mut formatter = SqlFormatter::new(config);
-    Ok(formatter.format_select(&ast))
+    let mut lines = Vec::new();
+
+    // input with no statements, e.g. a file of only comments, keeps just its
+    // comments
+    if statements.is_empty() {
+        formatter.comments = parser.take_remaining_comments();
+        formatter.flush_comments(usize::MAX, &mut lines);
+    }
+
+    for (index, (statement, terminated)) in statements.iter().enumerate() {
+        if index > 0 {
+            lines.push(String::new());
+        }
+        lines.push(formatter.format_statement(statement));
+        if *terminated {
+            if let Some(last) = lines.last_mut() {
+                last.push(';');
+            }
+        }
+
+        // whatever is left trails the statement
+        formatter.flush_comments(usize::MAX, &mut lines);
+    }
+
+    Ok(lines.join("\n"))
 }
 
-struct SqlFormatter<'a> {
-    config: &'a Config,
-    indent_level: usize,
-}
+struct SqlFormatter<'a> {
+    config: &'a Config,
+    indent_level: usize,
+    margin: String, // leading text every indent starts from, e.g. a select list's column
+    comments: Vec<Comment>, // comments not yet emitted, in source order
+}
+
+impl<'a> SqlFormatter<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self {
+            config,
+            indent_level: 0,
+            margin: String::new(),
+            comments: Vec::new(),
+        }
+    }
+
+    fn indent(&self) -> String {
+        format!(
+            "{}{}",
+            self.margin,
+            self.config
+                .indent_char
+                .repeat(self.indent_level * self.config.indent_width)
+        )
+    }
+
+    // emit every pending comment that starts before `position`. a comment that
+    // trailed code in the source stays at the end of the last line, anything
+    // else goes on its own line at the current indent.
+    fn flush_comments(&mut self, position: usize, lines: &mut Vec<String>) {
+        while self
+            .comments
+            .first()
+            .is_some_and(|comment| comment.span.start < position)
+        {
+            let comment = self.comments.remove(0);
+
+            match lines.last_mut() {
+                Some(line) if !comment.own_line => {
+                    line.push(' ');
+                    line.push_str(&comment.text);
+                }
+                _ => lines.push(format!("{}{}", self.indent(), comment.text)),
+            }
+        }
+    }
+
+    fn format_statement(&mut self, statement: &AST) -> String {
+        match statement {
+            AST::Query(query) => self.format_query(query),
+            AST::Insert(insert) => self.format_insert(insert),
+            AST::Update(update) => self.format_update(update),
+            AST::Delete(delete) => self.format_delete(delete),
+            AST::Merge(merge) => self.format_merge(merge),
+            AST::CreateTable(create) => self.format_create_table(create),
+            AST::AlterTable(alter) => self.format_alter_table(alter),
+            AST::Drop(drop) => self.format_drop(drop),
+            AST::Truncate(truncate) => self.format_truncate(truncate),
+        }
+    }
+
+    fn format_insert(&mut self, insert: &InsertStatement) -> String {
+        let mut parts = Vec::new();
+
+        self.comments.extend(insert.comments.iter().cloned());
+        self.comments.sort_by_key(|comment| comment.span.start);
+        self.flush_comments(insert.span.start, &mut parts);
+
+        let mut header = format!("{}INSERT INTO {}", self.indent(), insert.table);
+        if !insert.columns.is_empty() {
+            let columns = self.format_column_list(&insert.columns, header.len() + 1);
+            header.push(' ');
+            header.push_str(&columns);
+        }
+        parts.push(header);
+
+        if let Some(output) = &insert.returning {
+            if output.kind == ReturningKind::Output {
+                self.format_returning(output, &mut parts);
+            }
+        }
+
+        self.format_insert_source(&insert.source, &mut parts);
+
+        if let Some(upsert) = &insert.upsert {
+            self.format_upsert(upsert, &mut parts);
+        }
+
+        if let Some(returning) = &insert.returning {
+            if returning.kind == ReturningKind::Returning {
+                self.format_returning(returning, &mut parts);
+            }
+        }
+
+        self.flush_comments(insert.span.end, &mut parts);
+        parts.join("\n")
+    }
+
+    fn format_update(&mut self, update: &UpdateStatement) -> String {
+        let mut parts = Vec::new();
+
+        self.comments.extend(update.comments.iter().cloned());
+        self.comments.sort_by_key(|comment| comment.span.start);
+        self.flush_comments(update.span.start, &mut parts);
+
+        self.format_tables("UPDATE ", &update.tables, &mut parts);
+        self.format_assignments("SET ", &update.assignments, &mut parts);
+
+        if let Some(output) = &update.returning {
+            if output.kind == ReturningKind::Output {
+                self.format_returning(output, &mut parts);
+            }
+        }
+
+        if let Some(from) = &update.from {
+            self.format_tables("FROM ", from, &mut parts);
+        }
+
+        if let Some(where_clause) = &update.where_clause {
+            self.format_where(where_clause, &mut parts);
+        }
+
+        if let Some(returning) = &update.returning {
+            if returning.kind == ReturningKind::Returning {
+                self.format_returning(returning, &mut parts);
+            }
+        }
+
+        self.flush_comments(update.span.end, &mut parts);
+        parts.join("\n")
+    }
+
+    fn format_delete(&mut self, delete: &DeleteStatement) -> String {
+        let mut parts = Vec::new();
+
+        self.comments.extend(delete.comments.iter().cloned());
+        self.comments.sort_by_key(|comment| comment.span.start);
+        self.flush_comments(delete.span.start, &mut parts);
+
+        let mut keyword = "DELETE ".to_string();
+        if let Some(top) = &delete.top {
+            let top = self.format_expression(top);
+            keyword.push_str(&format!("TOP ({}) ", top));
+        }
+        // with a second FROM list the first one names the rows to delete
+        if delete.from.is_none() {
+            keyword.push_str("FROM ");
+        }
+        self.format_tables(&keyword, &delete.tables, &mut parts);
+
+        if let Some(output) = &delete.returning {
+            if output.kind == ReturningKind::Output {
+                self.format_returning(output, &mut parts);
+            }
+        }
+
+        if let Some(from) = &delete.from {
+            self.format_tables("FROM ", from, &mut parts);
+        }
+
+        if let Some(using) = &delete.using {
+            self.format_tables("USING ", using, &mut parts);
+        }
+
+        if let Some(where_clause) = &delete.where_clause {
+            self.format_where(where_clause, &mut parts);
+        }
+
+        if let Some(returning) = &delete.returning {
+            if returning.kind == ReturningKind::Returning {
+                self.format_returning(returning, &mut parts);
+            }
+        }
+
+        self.flush_comments(delete.span.end, &mut parts);
+        parts.join("\n")
+    }
+
+    fn format_insert_source(&mut self, source: &InsertSource, parts: &mut Vec<String>) {
+        match source {
+            InsertSource::DefaultValues => {
+                parts.push(format!("{}DEFAULT VALUES", self.indent()));
+            }
+            InsertSource::Values(rows) => self.format_values(rows, parts),
+            InsertSource::Query(query) => {
+                let query = self.format_query(query);
+                parts.push(query);
+            }
+        }
+    }
+
+    fn format_merge(&mut self, merge: &MergeStatement) -> String {
+        let mut parts = Vec::new();
+
+        self.comments.extend(merge.comments.iter().cloned());
+        self.comments.sort_by_key(|comment| comment.span.start);
+        self.flush_comments(merge.span.start, &mut parts);
+
+        let target = self.format_table_reference(&merge.target);
+        parts.push(format!("{}MERGE INTO {}", self.indent(), target));
+        self.format_tables("USING ", std::slice::from_ref(&merge.source), &mut parts);
+
+        // ON is right-aligned to the end of USING, like a join's
+        let indent = format!("{}   ", self.indent());
+        self.indent_level += 1;
+        let condition = self.format_condition(&indent, "ON", &merge.on);
+        self.indent_level -= 1;
+        parts.push(format!("{}ON {}", indent, condition));
+
+        for clause in &merge.clauses {
+            self.format_merge_clause(clause, &mut parts);
+        }
+
+        if let Some(returning) = &merge.returning {
+            self.format_returning(returning, &mut parts);
+        }
+
+        self.flush_comments(merge.span.end, &mut parts);
+        parts.join("\n")
+    }
+
+    // `WHEN ... THEN` with the action on the lines below, one level in
+    fn format_merge_clause(&mut self, clause: &MergeClause, parts: &mut Vec<String>) {
+        self.flush_comments(clause.span.start, parts);
+
+        let mut header = format!("{}WHEN {}", self.indent(), clause.kind);
+        if let Some(condition) = &clause.condition {
+            let condition = self.format_expression(condition);
+            header.push_str(&format!(" AND {}", condition));
+        }
+        header.push_str(" THEN");
+        parts.push(header);
+
+        self.indent_level += 1;
+        match &clause.action {
+            MergeAction::Update(assignments) => {
+                self.format_assignments("UPDATE SET ", assignments, parts);
+            }
+            MergeAction::Delete => parts.push(format!("{}DELETE", self.indent())),
+            MergeAction::DoNothing => parts.push(format!("{}DO NOTHING", self.indent())),
+            MergeAction::Insert { columns, source } => {
+                let mut insert = format!("{}INSERT", self.indent());
+                if !columns.is_empty() {
+                    let columns = self.format_column_list(columns, insert.len() + 1);
+                    insert.push(' ');
+                    insert.push_str(&columns);
+                }
+
+                if *source == InsertSource::DefaultValues {
+                    insert.push_str(" DEFAULT VALUES");
+                    parts.push(insert);
+                } else {
+                    parts.push(insert);
+                    self.format_insert_source(source, parts);
+                }
+            }
+        }
+        self.indent_level -= 1;
+    }
+
+    // one column or constraint per line, with the column names, types and
+    // constraints lined up
+    fn format_create_table(&mut self, create: &CreateTableStatement) -> String {
+        let mut parts = Vec::new();
+
+        self.comments.extend(create.comments.iter().cloned());
+        self.comments.sort_by_key(|comment| comment.span.start);
+        self.flush_comments(create.span.start, &mut parts);
+
+        let mut header = format!("{}CREATE ", self.indent());
+        if create.temporary {
+            header.push_str("TEMPORARY ");
+        }
+        header.push_str("TABLE ");
+        if create.if_not_exists {
+            header.push_str("IF NOT EXISTS ");
+        }
+        parts.push(format!("{}{} (", header, create.name));
+
+        let columns: Vec<(String, String)> = create
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                TableElement::Column(column) => Some((
+                    column.name.to_string(),
+                    self.format_data_type(&column.data_type),
+                )),
+                TableElement::Constraint(_) => None,
+            })
+            .collect();
+        let (name_width, type_width) = if self.config.align_columns {
+            columns
+                .iter()
+                .fold((0, 0), |(names, types), (name, data_type)| {
+                    (names.max(name.len()), types.max(data_type.len()))
+                })
+        } else {
+            (0, 0)
+        };
+
+        self.indent_level += 1;
+        let mut columns = columns.into_iter();
+        for (index, element) in create.elements.iter().enumerate() {
+            if index > 0 {
+                if let Some(previous) = parts.last_mut() {
+                    previous.push(',');
+                }
+            }
+            self.flush_comments(element.span().start, &mut parts);
+
+            let line = match element {
+                TableElement::Column(column) => {
+                    let (name, data_type) = columns.next().unwrap_or_default();
+                    let mut line = format!("{:<width$} {}", name, data_type, width = name_width);
+                    if !column.constraints.is_empty() {
+                        let constraints: Vec<String> = column
+                            .constraints
+                            .iter()
+                            .map(|constraint| self.format_column_constraint(constraint))
+                            .collect();
+                        line = format!(
+                            "{:<width$} {}",
+                            line,
+                            constraints.join(" "),
+                            width = name_width + 1 + type_width
+                        );
+                    }
+                    line
+                }
+                TableElement::Constraint(constraint) => self.format_table_constraint(constraint),
+            };
+            parts.push(format!("{}{}", self.indent(), line));
+        }
+        self.indent_level -= 1;
+
+        self.flush_comments(create.span.end, &mut parts);
+        parts.push(format!("{})", self.indent()));
+        parts.join("\n")
+    }
+
+    fn format_column_constraint(&mut self, constraint: &ColumnConstraint) -> String {
+        let kind = match &constraint.kind {
+            ColumnConstraintKind::NotNull => "NOT NULL".to_string(),
+            ColumnConstraintKind::Null => "NULL".to_string(),
+            ColumnConstraintKind::Default(value) => {
+                format!("DEFAULT {}", self.format_expression(value))
+            }
+            ColumnConstraintKind::PrimaryKey => "PRIMARY KEY".to_string(),
+            ColumnConstraintKind::Unique => "UNIQUE".to_string(),
+            ColumnConstraintKind::References(references) => self.format_references(references),
+            ColumnConstraintKind::Check(condition) => {
+                format!("CHECK ({})", self.format_expression(condition))
+            }
+            ColumnConstraintKind::Collate(collation) => format!("COLLATE {}", collation),
+            ColumnConstraintKind::Identity(None) => "IDENTITY".to_string(),
+            ColumnConstraintKind::Identity(Some((seed, increment))) => format!(
+                "IDENTITY({}, {})",
+                self.format_expression(seed),
+                self.format_expression(increment)
+            ),
+            ColumnConstraintKind::AutoIncrement => "AUTO_INCREMENT".to_string(),
+            ColumnConstraintKind::GeneratedIdentity { always: true } => {
+                "GENERATED ALWAYS AS IDENTITY".to_string()
+            }
+            ColumnConstraintKind::GeneratedIdentity { always: false } => {
+                "GENERATED BY DEFAULT AS IDENTITY".to_string()
+            }
+            ColumnConstraintKind::GeneratedAs { expr, stored } => {
+                let mut generated =
+                    format!("GENERATED ALWAYS AS ({})", self.format_expression(expr));
+                if *stored {
+                    generated.push_str(" STORED");
+                }
+                generated
+            }
+        };
+
+        match &constraint.name {
+            Some(name) => format!("CONSTRAINT {} {}", name, kind),
+            None => kind,
+        }
+    }
+
+    // a CHECK made of several conditions puts each on its own line inside
+    // the parens, one level in
+    fn format_table_constraint(&mut self, constraint: &TableConstraint) -> String {
+        let kind = self.format_table_constraint_kind(&constraint.kind);
+        match &constraint.name {
+            Some(name) => format!("CONSTRAINT {} {}", name, kind),
+            None => kind,
+        }
+    }
+
+    fn format_table_constraint_kind(&mut self, kind: &TableConstraintKind) -> String {
+        let columns = |columns: &[Identifier]| {
+            let columns: Vec<String> = columns.iter().map(|column| column.to_string()).collect();
+            format!("({})", columns.join(", "))
+        };
+
+        match kind {
+            TableConstraintKind::PrimaryKey(keys) => format!("PRIMARY KEY {}", columns(keys)),
+            TableConstraintKind::Unique(keys) => format!("UNIQUE {}", columns(keys)),
+            TableConstraintKind::ForeignKey {
+                columns: keys,
+                references,
+            } => format!(
+                "FOREIGN KEY {} {}",
+                columns(keys),
+                self.format_references(references)
+            ),
+            TableConstraintKind::Check(
+                condition @ Expression::BinaryOperation {
+                    op: Operator::And | Operator::Or,
+                    ..
+                },
+            ) => {
+                let close = self.indent();
+                self.indent_level += 1;
+                let indent = self.indent();
+                let condition = self.format_condition(&indent, "AND", condition);
+                self.indent_level -= 1;
+                format!("CHECK (\n{}{}\n{})", indent, condition, close)
+            }
+            TableConstraintKind::Check(condition) => {
+                format!("CHECK ({})", self.format_expression(condition))
+            }
+        }
+    }
+
+    // one action per line a level in. the action keywords, the names they act
+    // on and the rest of each action are lined up in columns
+    fn format_alter_table(&mut self, alter: &AlterTableStatement) -> String {
+        let mut parts = Vec::new();
+
+        self.comments.extend(alter.comments.iter().cloned());
+        self.comments.sort_by_key(|comment| comment.span.start);
+        self.flush_comments(alter.span.start, &mut parts);
+
+        let if_exists = if alter.if_exists { "IF EXISTS " } else { "" };
+        parts.push(format!(
+            "{}ALTER TABLE {}{}",
+            self.indent(),
+            if_exists,
+            alter.name
+        ));
+
+        self.indent_level += 1;
+        let actions: Vec<[String; 3]> = alter
+            .actions
+            .iter()
+            .map(|action| self.format_alter_table_action(&action.kind))
+            .collect();
+        let (keyword_width, name_width) = if self.config.align_columns {
+            actions
+                .iter()
+                .fold((0, 0), |(keywords, names), [keyword, name, _]| {
+                    (keywords.max(keyword.len()), names.max(name.len()))
+                })
+        } else {
+            (0, 0)
+        };
+
+        for (index, (action, [keyword, name, rest])) in
+            alter.actions.iter().zip(actions).enumerate()
+        {
+            if index > 0 {
+                if let Some(previous) = parts.last_mut() {
+                    previous.push(',');
+                }
+            }
+            self.flush_comments(action.span.start, &mut parts);
+
+            let mut line = format!("{:<width$} {}", keyword, name, width = keyword_width);
+            if !rest.is_empty() {
+                line = format!(
+                    "{:<width$} {}",
+                    line,
+                    rest,
+                    width = keyword_width + 1 + name_width
+                );
+            }
+            parts.push(format!("{}{}", self.indent(), line.trim_end()));
+        }
+        self.indent_level -= 1;
+
+        self.flush_comments(alter.span.end, &mut parts);
+        parts.join("\n")
+    }
+
+    // the keyword, name and remainder columns of an ALTER TABLE action
+    fn format_alter_table_action(&mut self, kind: &AlterTableActionKind) -> [String; 3] {
+        let if_exists = |if_exists: bool| if if_exists { " IF EXISTS" } else { "" };
+        let behavior = |behavior: &Option<DropBehavior>| {
+            behavior
+                .map(|behavior| behavior.to_string())
+                .unwrap_or_default()
+        };
+
+        match kind {
+            AlterTableActionKind::AddColumn {
+                column_keyword,
+                if_not_exists,
+                column,
+            } => {
+                let mut keyword = "ADD".to_string();
+                if *column_keyword {
+                    keyword.push_str(" COLUMN");
+                }
+                if *if_not_exists {
+                    keyword.push_str(" IF NOT EXISTS");
+                }
+
+                let mut definition = self.format_data_type(&column.data_type);
+                for constraint in &column.constraints {
+                    definition.push(' ');
+                    definition.push_str(&self.format_column_constraint(constraint));
+                }
+                [keyword, column.name.to_string(), definition]
+            }
+            AlterTableActionKind::DropColumn {
+                if_exists: exists,
+                name,
+                behavior: drop,
+            } => [
+                format!("DROP COLUMN{}", if_exists(*exists)),
+                name.to_string(),
+                behavior(drop),
+            ],
+            AlterTableActionKind::AlterColumn { name, change } => {
+                let change = match change {
+                    AlterColumnChange::SetDataType(data_type) => {
+                        format!("TYPE {}", self.format_data_type(data_type))
+                    }
+                    AlterColumnChange::SetDefault(value) => {
+                        format!("SET DEFAULT {}", self.format_expression(value))
+                    }
+                    AlterColumnChange::DropDefault => "DROP DEFAULT".to_string(),
+                    AlterColumnChange::SetNotNull => "SET NOT NULL".to_string(),
+                    AlterColumnChange::DropNotNull => "DROP NOT NULL".to_string(),
+                };
+                ["ALTER COLUMN".to_string(), name.to_string(), change]
+            }
+            AlterTableActionKind::AddConstraint(constraint) => {
+                let kind = self.format_table_constraint_kind(&constraint.kind);
+                match &constraint.name {
+                    Some(name) => ["ADD CONSTRAINT".to_string(), name.to_string(), kind],
+                    None => ["ADD".to_string(), String::new(), kind],
+                }
+            }
+            AlterTableActionKind::DropConstraint {
+                if_exists: exists,
+                name,
+                behavior: drop,
+            } => [
+                format!("DROP CONSTRAINT{}", if_exists(*exists)),
+                name.to_string(),
+                behavior(drop),
+            ],
+            AlterTableActionKind::RenameColumn { old, new } => [
+                "RENAME COLUMN".to_string(),
+                old.to_string(),
+                format!("TO {}", new),
+            ],
+            AlterTableActionKind::RenameTable(name) => {
+                ["RENAME TO".to_string(), name.to_string(), String::new()]
+            }
+        }
+    }
+
+    fn format_drop(&mut self, drop: &DropStatement) -> String {
+        let mut parts = Vec::new();
+
+        self.comments.extend(drop.comments.iter().cloned());
+        self.comments.sort_by_key(|comment| comment.span.start);
+        self.flush_comments(drop.span.start, &mut parts);
+
+        let mut line = format!("{}DROP {} ", self.indent(), drop.object_type);
+        if drop.if_exists {
+            line.push_str("IF EXISTS ");
+        }
+        let names: Vec<String> = drop.names.iter().map(|name| name.to_string()).collect();
+        line.push_str(&names.join(", "));
+        if let Some(table) = &drop.table {
+            line.push_str(&format!(" ON {}", table));
+        }
+        if let Some(behavior) = drop.behavior {
+            line.push_str(&format!(" {}", behavior));
+        }
+        parts.push(line);
+
+        self.flush_comments(drop.span.end, &mut parts);
+        parts.join("\n")
+    }
+
+    fn format_truncate(&mut self, truncate: &TruncateStatement) -> String {
+        let mut parts = Vec::new();
+
+        self.comments.extend(truncate.comments.iter().cloned());
+        self.comments.sort_by_key(|comment| comment.span.start);
+        self.flush_comments(truncate.span.start, &mut parts);
+
+        let tables: Vec<String> = truncate
+            .tables
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let mut line = format!("{}TRUNCATE TABLE {}", self.indent(), tables.join(", "));
+        if truncate.restart_identity {
+            line.push_str(" RESTART IDENTITY");
+        }
+        if let Some(behavior) = truncate.behavior {
+            line.push_str(&format!(" {}", behavior));
+        }
+        parts.push(line);
+
+        self.flush_comments(truncate.span.end, &mut parts);
+        parts.join("\n")
+    }
+
+    fn format_references(&mut self, references: &ForeignKeyReference) -> String {
+        let mut result = format!("REFERENCES {}", references.table);
+        if !references.columns.is_empty() {
+            let columns: Vec<String> = references
+                .columns
+                .iter()
+                .map(|column| column.to_string())
+                .collect();
+            result.push_str(&format!("({})", columns.join(", ")));
+        }
+        if let Some(action) = references.on_delete {
+            result.push_str(&format!(" ON DELETE {}", action));
+        }
+        if let Some(action) = references.on_update {
+            result.push_str(&format!(" ON UPDATE {}", action));
+        }
+        result
+    }
+
+    // a parenthesized column list. it stays on one line if it fits after
+    // `offset` characters, otherwise each column goes on its own line two
+    // levels in, with the closing paren one level in
+    fn format_column_list(&mut self, columns: &[Identifier], offset: usize) -> String {
+        let columns: Vec<String> = columns.iter().map(|column| column.to_string()).collect();
+
+        let inline = format!("({})", columns.join(", "));
+        if offset + inline.len() <= self.config.max_line_length {
+            return inline;
+        }
+
+        self.indent_level += 2;
+        let inner = self.indent();
+        self.indent_level -= 1;
+        let close = self.indent();
+        self.indent_level -= 1;
+
+        let lines: Vec<String> = columns
+            .iter()
+            .map(|column| format!("{}{}", inner, column))
+            .collect();
+        format!("(\n{}\n{})", lines.join(",\n"), close)
+    }
+
+    // VALUES rows, one per line, with the values lined up column by column
+    fn format_values(&mut self, source_rows: &[Vec<Expression>], parts: &mut Vec<String>) {
+        let rows: Vec<Vec<String>> = source_rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|value| self.format_expression(value))
+                    .collect()
+            })
+            .collect();
+
+        // values spanning several lines are left out of the widths
+        let column_count = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..column_count)
+            .map(|column| {
+                rows.iter()
+                    .filter_map(|row| row.get(column))
+                    .filter(|value| !value.contains('\n'))
+                    .map(|value| value.len())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        for (index, (row, source)) in rows.iter().zip(source_rows).enumerate() {
+            if let Some(first) = source.first() {
+                self.flush_comments(first.span().start, parts);
+            }
+
+            let values: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(column, value)| {
+                    if column + 1 < row.len() {
+                        format!(
+                            "{:<width$}",
+                            format!("{},", value),
+                            width = widths[column] + 1
+                        )
+                    } else {
+                        value.clone()
+                    }
+                })
+                .collect();
+
+            let keyword = if index == 0 { "VALUES " } else { "       " };
+            let comma = if index + 1 < rows.len() { "," } else { "" };
+            parts.push(format!(
+                "{}{}({}){}",
+                self.indent(),
+                keyword,
+                values.join(" "),
+                comma
+            ));
+        }
+    }
+
+    fn format_upsert(&mut self, upsert: &Upsert, parts: &mut Vec<String>) {
+        self.flush_comments(upsert.span().start, parts);
+
+        match upsert {
+            Upsert::OnConflict { target, action, .. } => {
+                let mut line = format!("{}ON CONFLICT", self.indent());
+                match target {
+                    Some(ConflictTarget::Columns(columns)) => {
+                        let columns: Vec<String> =
+                            columns.iter().map(|column| column.to_string()).collect();
+                        line.push_str(&format!(" ({})", columns.join(", ")));
+                    }
+                    Some(ConflictTarget::Constraint(name)) => {
+                        line.push_str(&format!(" ON CONSTRAINT {}", name));
+                    }
+                    None => {}
+                }
+
+                match action {
+                    ConflictAction::DoNothing => parts.push(format!("{} DO NOTHING", line)),
+                    ConflictAction::DoUpdate {
+                        assignments,
+                        where_clause,
+                    } => {
+                        parts.push(format!("{} DO UPDATE", line));
+                        self.format_assignments("SET ", assignments, parts);
+
+                        if let Some(condition) = where_clause {
+                            let indent = self.indent();
+                            self.indent_level += 1;
+                            let condition = self.format_condition(&indent, "WHERE", condition);
+                            self.indent_level -= 1;
+                            parts.push(format!("{}WHERE {}", indent, condition));
+                        }
+                    }
+                }
+            }
+            Upsert::OnDuplicateKeyUpdate { assignments, .. } => {
+                self.format_assignments("ON DUPLICATE KEY UPDATE ", assignments, parts);
+            }
+        }
+    }
+
+    // `keyword` followed by `column = value` pairs, one per line, lined up
+    // under the first
+    fn format_assignments(
+        &mut self,
+        keyword: &str,
+        assignments: &[Assignment],
+        parts: &mut Vec<String>,
+    ) {
+        // the `=` signs line up after the longest column, like select aliases
+        let padding = if self.config.align_columns {
+            assignments
+                .iter()
+                .map(|assignment| assignment.column.to_string().len())
+                .max()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        for (index, assignment) in assignments.iter().enumerate() {
+            if index > 0 {
+                if let Some(previous) = parts.last_mut() {
+                    previous.push(',');
+                }
+            }
+            self.flush_comments(assignment.span.start, parts);
+
+            self.indent_level += 1;
+            let value = self.format_expression(&assignment.value);
+            self.indent_level -= 1;
 
-impl<'a> SqlFormatter<'a> {
-    pub fn new(config: &'a Config) -> Self {
-        Self {
-            config,
-            indent_level: 0,
+            let keyword = if index == 0 {
+                keyword.to_string()
+            } else {
+                " ".repeat(keyword.len())
+            };
+            let column = assignment.column.to_string();
+            parts.push(format!(
+                "{}{}{}{} {} {}",
+                self.indent(),
+                keyword,
+                column,
+                " ".repeat(padding.saturating_sub(column.len())),
+                assignment.operator,
+                value
+            ));
         }
     }
 
-    fn indent(&self) -> String {
-        self.config
-            .indent_char
-            .repeat(self.indent_level * self.config.indent_width)
+    fn format_returning(&mut self, returning: &Returning, parts: &mut Vec<String>) {
+        self.flush_comments(returning.span.start, parts);
+        let keyword = format!("{} ", returning.kind);
+        let items = self.format_select_list(&keyword, &returning.items);
+        parts.push(items);
     }
 
-    fn format_select(&mut self, stmt: &SelectStatement) -> String {
+    fn format_query(&mut self, query: &Query) -> String {
         let mut parts = Vec::new();
 
-        // SELECT clause with first item
-        self.indent_level += 1;
-        let columns = self.format_select_items(&stmt.columns);
-        self.indent_level -= 1;
+        self.comments.extend(query.comments.iter().cloned());
+        self.comments.sort_by_key(|comment| comment.span.start);
 
-        // if we don't have any columns, crash out.
-        if columns.is_empty() {
-            return String::new();
+        if let Some(with) = &query.with {
+            self.format_with(with, &mut parts);
         }
 
-        // split the columns into the first line and subsequent lines
-        let column_lines: Vec<&str> = columns.split('\n').collect();
-        let select_line = format!("{}SELECT {}", self.indent(), column_lines[0].trim_start());
-        parts.push(select_line);
+        self.format_set_expression(&query.body, &mut parts);
 
-        // add the rest of the columns accounting for the indent level if
-        // left align select is enbled.
+        // ORDER BY clause
+        if let Some(order_by) = &query.order_by {
+            if let Some(first) = order_by.first() {
+                self.flush_comments(first.span.start, &mut parts);
+            }
+            let items = self.format_order_by_list(order_by);
+            parts.push(format!("{}ORDER BY {}", self.indent(), items));
+        }
 
-        let base_indent = self.indent();
-        let column_padding = if self.config.align_columns {
-            ' '.to_string().repeat(3)
-        } else {
-            "".to_string()
+        // LIMIT / OFFSET / FETCH
+        if let Some(limit) = &query.limit {
+            self.flush_comments(limit.span().start, &mut parts);
+            let limit = self.format_expression(limit);
+            parts.push(format!("{}LIMIT {}", self.indent(), limit));
+        }
+
+        if let Some(offset) = &query.offset {
+            self.flush_comments(offset.span.start, &mut parts);
+            let mut line = format!(
+                "{}OFFSET {}",
+                self.indent(),
+                self.format_expression(&offset.value)
+            );
+            if let Some(rows) = &offset.rows {
+                line.push_str(&format!(" {}", rows));
+            }
+            parts.push(line);
+        }
+
+        if let Some(fetch) = &query.fetch {
+            self.flush_comments(fetch.span.start, &mut parts);
+            let mut line = format!(
+                "{}FETCH {} ",
+                self.indent(),
+                if fetch.next { "NEXT" } else { "FIRST" }
+            );
+            if let Some(quantity) = &fetch.quantity {
+                line.push_str(&format!("{} ", self.format_expression(quantity)));
+            }
+            line.push_str(&format!("{} ONLY", fetch.rows));
+            parts.push(line);
+        }
+
+        self.flush_comments(query.span.end, &mut parts);
+
+        parts.join("\n")
+    }
+
+    // SELECTs and the set operators between them, each operator on its own line
+    fn format_set_expression(&mut self, expr: &SetExpression, parts: &mut Vec<String>) {
+        match expr {
+            SetExpression::Select(select) => self.format_select(select, parts),
+            SetExpression::Query(query) => {
+                self.flush_comments(query.span.start, parts);
+                let query = self.format_subquery(query);
+                parts.push(format!("{}{}", self.indent(), query));
+            }
+            SetExpression::Operation {
+                left,
+                op,
+                quantifier,
+                right,
+                ..
+            } => {
+                self.format_set_expression(left, parts);
+                self.flush_comments(right.span().start, parts);
+
+                let mut line = format!("{}{}", self.indent(), op);
+                if let Some(quantifier) = quantifier {
+                    line.push_str(&format!(" {}", quantifier));
+                }
+                parts.push(line);
+
+                self.format_set_expression(right, parts);
+            }
+        }
+    }
+
+    fn format_select(&mut self, stmt: &SelectStatement, parts: &mut Vec<String>) {
+        // comments ahead of the first column lead the whole statement
+        if let Some(first) = stmt.columns.first() {
+            self.flush_comments(first.span().start, parts);
+        }
+
+        let keyword = match &stmt.quantifier {
+            Some(SelectQuantifier::All) => "SELECT ALL ".to_string(),
+            Some(SelectQuantifier::Distinct) => "SELECT DISTINCT ".to_string(),
+            Some(SelectQuantifier::DistinctOn(expressions)) => {
+                let expressions: Vec<String> = expressions
+                    .iter()
+                    .map(|expr| self.format_expression(expr))
+                    .collect();
+                format!("SELECT DISTINCT ON ({}) ", expressions.join(", "))
+            }
+            None => "SELECT ".to_string(),
         };
 
-        for line in column_lines.iter().skip(1) {
-            parts.push(format!("{}{}{}", base_indent, column_padding, line));
+        // if we don't have any columns, crash out.
+        if stmt.columns.is_empty() {
+            return;
         }
 
+        let columns = self.format_select_list(&keyword, &stmt.columns);
+        parts.push(columns);
+
         // FROM clause
-        parts.push(format!(
-            "{}FROM {}",
-            self.indent(),
-            self.format_table_reference(&stmt.from)
-        ));
+        if let Some(from) = &stmt.from {
+            self.format_tables("FROM ", from, parts);
+        }
 
         // WHERE clause
         if let Some(where_clause) = &stmt.where_clause {
-            parts.push(format!(
-                "{}WHERE {}",
-                self.indent(),
-                self.format_expression(&where_clause.condition)
-            ));
+            self.format_where(where_clause, parts);
         }
 
         // GROUP BY clause
         if let Some(group_by) = &stmt.group_by {
+            if let Some(first) = group_by.first() {
+                self.flush_comments(first.span().start, parts);
+            }
             let expressions: Vec<String> = group_by
                 .iter()
                 .map(|expr| self.format_expression(expr))
@@ -91,7 +1007,59 @@ impl<'a> SqlFormatter<'a> {
             ));
         }
 
-        parts.join("\n")
+        // HAVING clause
+        if let Some(having) = &stmt.having {
+            self.flush_comments(having.span().start, parts);
+            let indent = self.indent();
+            self.indent_level += 1;
+            let condition = self.format_condition(&indent, "HAVING", having);
+            self.indent_level -= 1;
+            parts.push(format!("{}HAVING {}", indent, condition));
+        }
+
+        // WINDOW clause, later windows lined up under the first
+        if let Some(windows) = &stmt.window {
+            for (index, window) in windows.iter().enumerate() {
+                let keyword = if index == 0 { "WINDOW " } else { "       " };
+                if index > 0 {
+                    if let Some(previous) = parts.last_mut() {
+                        previous.push(',');
+                    }
+                }
+                self.flush_comments(window.span.start, parts);
+
+                let prefix = format!("{}{}{} AS ", self.indent(), keyword, window.name);
+                let spec =
+                    self.format_window_spec(&window.spec, prefix.len() - self.indent().len());
+                parts.push(format!("{}{}", prefix, spec));
+            }
+        }
+
+        self.flush_comments(stmt.span.end, parts);
+    }
+
+    // `keyword` and the items after it, e.g. `SELECT a, b` or `RETURNING id`.
+    // items line up after the keyword, or sit a level in when they aren't
+    // aligned. that column is the margin while the items are laid out
+    fn format_select_list(&mut self, keyword: &str, items: &[SelectItem]) -> String {
+        let base_indent = self.indent();
+        let column_indent = if self.config.align_columns {
+            format!("{}{}", base_indent, " ".repeat(keyword.len()))
+        } else {
+            format!(
+                "{}{}",
+                base_indent,
+                self.config.indent_char.repeat(self.config.indent_width)
+            )
+        };
+
+        let margin = std::mem::replace(&mut self.margin, column_indent);
+        let indent_level = std::mem::replace(&mut self.indent_level, 0);
+        let columns = self.format_select_items(items);
+        self.margin = margin;
+        self.indent_level = indent_level;
+
+        format!("{}{}{}", base_indent, keyword, columns)
     }
 
     fn format_select_items(&mut self, items: &[SelectItem]) -> String {
@@ -102,76 +1070,90 @@ impl<'a> SqlFormatter<'a> {
         let mut alias_padding = 0;
 
         // if select columns are aligned, aliases are padded to be left aligned
-        // after the longest column name.
+        // after the longest column name. items spanning several lines, like
+        // subqueries, are left out. measuring formats every item an extra
+        // time, so any comments emitted along the way are put back after.
         if self.config.align_columns {
+            let pending = self.comments.clone();
             alias_padding = items
                 .iter()
                 .map(|item| match item {
                     SelectItem::Wildcard { .. } => 1,
-                    SelectItem::QualifiedWildcard { qualifier, .. } => qualifier.len() + 2,
-                    SelectItem::Expression { expr, .. } => self.format_expression(expr).len(),
+                    SelectItem::QualifiedWildcard { qualifier, .. } => {
+                        qualifier.to_string().len() + 2
+                    }
+                    SelectItem::Expression { expr, .. } => {
+                        let expression = self.format_expression(expr);
+                        if expression.contains('\n') {
+                            0
+                        } else {
+                            expression.len()
+                        }
+                    }
                 })
                 .max()
-                .expect("Failed to get max alias length")
+                .expect("Failed to get max alias length");
+            self.comments = pending;
         }
 
-        let formatted: Vec<String> = items
-            .iter()
-            .enumerate()
-            .map(|(index, item)| {
-                let item_str = match item {
-                    SelectItem::Wildcard { .. } => "*".to_string(),
-                    SelectItem::QualifiedWildcard { qualifier, .. } => {
-                        format!("{}.*", qualifier)
-                    }
-                    SelectItem::Expression { expr, alias, .. } => {
-                        let expression = self.format_expression(expr);
+        let mut lines: Vec<String> = Vec::new();
 
-                        if let Some(alias_name) = alias {
-                            if self.config.align_columns {
-                                format!(
-                                    "{}{} AS {}",
-                                    expression,
-                                    " ".repeat(alias_padding - expression.len()),
-                                    alias_name
-                                )
-                            } else {
-                                format!("{} AS {}", expression, alias_name)
-                            }
+        for (index, item) in items.iter().enumerate() {
+            let item_str = match item {
+                SelectItem::Wildcard { .. } => "*".to_string(),
+                SelectItem::QualifiedWildcard { qualifier, .. } => {
+                    format!("{}.*", qualifier)
+                }
+                SelectItem::Expression { expr, alias, .. } => {
+                    let expression = self.format_expression(expr);
+
+                    if let Some(alias_name) = alias {
+                        if self.config.align_columns && !expression.contains('\n') {
+                            format!(
+                                "{}{} AS {}",
+                                expression,
+                                " ".repeat(alias_padding - expression.len()),
+                                alias_name
+                            )
                         } else {
-                            self.format_expression(expr)
+                            format!("{} AS {}", expression, alias_name)
                         }
+                    } else {
+                        expression
                     }
-                };
+                }
+            };
 
-                // for the first item, don't add indent - it'll be handeld in format_select
-                // for subsequent items, add indent to align with first item's stgarting position
-                if index == 0 {
-                    item_str
-                } else {
-                    format!("{}{}", self.indent(), item_str)
+            // for the first item, don't add indent - it'll be handeld in format_select
+            // for subsequent items, add indent to align with first item's stgarting position
+            if index == 0 {
+                lines.push(item_str);
+            } else {
+                // close off the previous item before any comments that follow it
+                if let Some(previous) = lines.last_mut() {
+                    previous.push(',');
                 }
-            })
-            .collect();
+                self.flush_comments(item.span().start, &mut lines);
+                lines.push(format!("{}{}", self.indent(), item_str));
+            }
+        }
 
-        formatted.join(",\n")
+        lines.join("\n")
     }
 
     fn format_expression(&mut self, expr: &Expression) -> String {
         match expr {
-            Expression::Column { name, table, .. } => {
-                if let Some(table_name) = table {
-                    format!("{}.{}", table_name, name)
-                } else {
-                    name.clone()
-                }
-            }
+            Expression::Column { name, .. } => name.to_string(),
             Expression::Literal { value, .. } => match value {
-                LiteralValue::String(s) => format!("'{}'", s),
-                LiteralValue::Number(n) => n.clone(),
-                LiteralValue::Boolean(b) => b.to_string(),
+                LiteralValue::String { raw, .. } => raw.clone(),
+                LiteralValue::Number(n) | LiteralValue::Hex(n) | LiteralValue::Binary(n) => {
+                    n.clone()
+                }
+                LiteralValue::Boolean(true) => "TRUE".to_string(),
+                LiteralValue::Boolean(false) => "FALSE".to_string(),
                 LiteralValue::Null => "NULL".to_string(),
             },
+            Expression::Default { .. } => "DEFAULT".to_string(),
             Expression::Asterisk { span: _ } => "*".to_string(),
             Expression::BinaryOperation {
                 left, op, right, ..
@@ -183,23 +1165,629 @@ impl<'a> SqlFormatter<'a> {
                     self.format_expression(right)
                 )
             }
-            Expression::Function { name, args, .. } => {
+            Expression::Function {
+                name,
+                args,
+                quantifier,
+                order_by,
+                within_group,
+                filter,
+                over,
+                ..
+            } => {
                 let formatted_args: Vec<String> =
                     args.iter().map(|arg| self.format_expression(arg)).collect();
-                format!("{}({})", name, formatted_args.join(", "))
+                let mut inner = formatted_args.join(", ");
+
+                if let Some(quantifier) = quantifier {
+                    inner = format!("{} {}", quantifier, inner);
+                }
+                if !order_by.is_empty() {
+                    inner.push_str(&format!(
+                        " ORDER BY {}",
+                        self.format_order_by_list(order_by)
+                    ));
+                }
+
+                let mut result = format!("{}({})", name, inner);
+
+                if !within_group.is_empty() {
+                    result.push_str(&format!(
+                        " WITHIN GROUP (ORDER BY {})",
+                        self.format_order_by_list(within_group)
+                    ));
+                }
+                if let Some(filter) = filter {
+                    result.push_str(&format!(
+                        " FILTER (WHERE {})",
+                        self.format_expression(filter)
+                    ));
+                }
+
+                match over {
+                    Some(Window::Named(name)) => result.push_str(&format!(" OVER {}", name)),
+                    Some(Window::Specification(spec)) => {
+                        result.push_str(" OVER ");
+                        let spec = self.format_window_spec(spec, result.len());
+                        result.push_str(&spec);
+                    }
+                    None => {}
+                }
+
+                result
+            }
+            Expression::UnaryOperation {
+                op: UnaryOperator::Not,
+                expr,
+                ..
+            } => format!("NOT {}", self.format_expression(expr)),
+            Expression::UnaryOperation { op, expr, .. } => {
+                // `- -1` written as `--1` would start a comment
+                let operand = self.format_expression(expr);
+                let space = matches!(**expr, Expression::UnaryOperation { .. })
+                    || operand.starts_with(['-', '+']);
+                format!("{}{}{}", op, if space { " " } else { "" }, operand)
+            }
+            Expression::Nested { expr, .. } => format!("({})", self.format_expression(expr)),
+            Expression::Subquery { query, .. } => self.format_subquery(query),
+            Expression::Exists { query, .. } => format!("EXISTS {}", self.format_subquery(query)),
+            Expression::InSubquery {
+                expr,
+                query,
+                negated,
+                ..
+            } => format!(
+                "{} {}IN {}",
+                self.format_expression(expr),
+                if *negated { "NOT " } else { "" },
+                self.format_subquery(query)
+            ),
+            Expression::InList {
+                expr,
+                list,
+                negated,
+                ..
+            } => {
+                let prefix = format!(
+                    "{} {}IN ",
+                    self.format_expression(expr),
+                    if *negated { "NOT " } else { "" }
+                );
+                let list = self.format_in_list(list, prefix.len());
+                format!("{}{}", prefix, list)
             }
+            Expression::Between {
+                expr,
+                low,
+                high,
+                negated,
+                ..
+            } => format!(
+                "{} {}BETWEEN {} AND {}",
+                self.format_expression(expr),
+                if *negated { "NOT " } else { "" },
+                self.format_expression(low),
+                self.format_expression(high)
+            ),
+            Expression::Like {
+                expr,
+                kind,
+                pattern,
+                escape,
+                negated,
+                ..
+            } => {
+                let mut result = format!(
+                    "{} {}{} {}",
+                    self.format_expression(expr),
+                    if *negated { "NOT " } else { "" },
+                    kind,
+                    self.format_expression(pattern)
+                );
+                if let Some(escape) = escape {
+                    result.push_str(&format!(" ESCAPE {}", self.format_expression(escape)));
+                }
+                result
+            }
+            Expression::Is {
+                expr,
+                value,
+                negated,
+                ..
+            } => format!(
+                "{} IS {}{}",
+                self.format_expression(expr),
+                if *negated { "NOT " } else { "" },
+                value
+            ),
+            Expression::IsDistinctFrom {
+                left,
+                right,
+                negated,
+                ..
+            } => format!(
+                "{} IS {}DISTINCT FROM {}",
+                self.format_expression(left),
+                if *negated { "NOT " } else { "" },
+                self.format_expression(right)
+            ),
+            Expression::Subscript { expr, index, .. } => format!(
+                "{}[{}]",
+                self.format_expression(expr),
+                self.format_expression(index)
+            ),
+            // postgres casts read as part of the value, so they're kept tight
+            Expression::Cast {
+                kind: CastKind::DoubleColon,
+                expr,
+                data_type,
+                ..
+            } => format!(
+                "{}::{}",
+                self.format_expression(expr),
+                self.format_data_type(data_type)
+            ),
+            Expression::Cast {
+                kind: kind @ (CastKind::Cast | CastKind::TryCast),
+                expr,
+                data_type,
+                ..
+            } => format!(
+                "{}({} AS {})",
+                if *kind == CastKind::TryCast {
+                    "TRY_CAST"
+                } else {
+                    "CAST"
+                },
+                self.format_expression(expr),
+                self.format_data_type(data_type)
+            ),
+            Expression::Cast {
+                kind,
+                expr,
+                data_type,
+                style,
+                ..
+            } => {
+                let mut args = vec![
+                    self.format_data_type(data_type),
+                    self.format_expression(expr),
+                ];
+                if let Some(style) = style {
+                    args.push(self.format_expression(style));
+                }
+                let name = if *kind == CastKind::TryConvert {
+                    "TRY_CONVERT"
+                } else {
+                    "CONVERT"
+                };
+                format!("{}({})", name, args.join(", "))
+            }
+            Expression::Case {
+                operand,
+                conditions,
+                else_result,
+                ..
+            } => self.format_case(operand.as_deref(), conditions, else_result.as_deref()),
         }
     }
 
-    fn format_table_reference(&mut self, table: &TableReference) -> String {
-        let mut result = String::new();
+    // each CTE starts a line, its query an indented block under it
+    fn format_with(&mut self, with: &With, parts: &mut Vec<String>) {
+        for (index, cte) in with.ctes.iter().enumerate() {
+            // close off the previous CTE before any comments that follow it
+            if index > 0 {
+                if let Some(previous) = parts.last_mut() {
+                    previous.push(',');
+                }
+            }
+            self.flush_comments(cte.span.start, parts);
+
+            let mut line = self.indent();
+            if index == 0 {
+                line.push_str(if with.recursive {
+                    "WITH RECURSIVE "
+                } else {
+                    "WITH "
+                });
+            }
+            line.push_str(&cte.name.to_string());
+
+            if !cte.columns.is_empty() {
+                let columns: Vec<String> = cte
+                    .columns
+                    .iter()
+                    .map(|column| column.to_string())
+                    .collect();
+                line.push_str(&format!(" ({})", columns.join(", ")));
+            }
+
+            line.push_str(" AS ");
+            if let Some(materialized) = &cte.materialized {
+                line.push_str(&format!("{} ", materialized));
+            }
+
+            line.push_str(&self.format_subquery(&cte.query));
+            parts.push(line);
+        }
+    }
+
+    // a parenthesized subquery. its body sits a level in from the current
+    // indent, with the closing paren back at the current indent
+    fn format_subquery(&mut self, query: &Query) -> String {
+        self.indent_level += 1;
+        let body = self.format_query(query);
+        self.indent_level -= 1;
+
+        format!("(\n{}\n{})", body, self.indent())
+    }
+
+    // a parenthesized IN list. it stays on one line if it fits after `offset`
+    // characters, otherwise the values fill lines a level in, like a subquery
+    fn format_in_list(&mut self, list: &[Expression], offset: usize) -> String {
+        self.indent_level += 1;
+        let inner = self.indent();
+        let values: Vec<String> = list
+            .iter()
+            .map(|value| self.format_expression(value))
+            .collect();
+        self.indent_level -= 1;
+
+        let inline = format!("({})", values.join(", "));
+        if !inline.contains('\n')
+            && self.indent().len() + offset + inline.len() <= self.config.max_line_length
+        {
+            return inline;
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut line = String::new();
+        for (index, value) in values.iter().enumerate() {
+            let value = if index + 1 < values.len() {
+                format!("{},", value)
+            } else {
+                value.clone()
+            };
+
+            if line.is_empty() {
+                line = format!("{}{}", inner, value);
+            } else if line.len() + 1 + value.len() <= self.config.max_line_length {
+                line.push(' ');
+                line.push_str(&value);
+            } else {
+                lines.push(std::mem::replace(&mut line, format!("{}{}", inner, value)));
+            }
+        }
+        lines.push(line);
+
+        format!("(\n{}\n{})", lines.join("\n"), self.indent())
+    }
+
+    // type names keep their spelling; arguments are spaced like a call's
+    fn format_data_type(&self, data_type: &DataType) -> String {
+        let mut result = data_type.name.to_string();
+
+        if !data_type.arguments.is_empty() {
+            let arguments: Vec<String> = data_type
+                .arguments
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect();
+            result.push_str(&format!("({})", arguments.join(", ")));
+        }
+        if let Some(time_zone) = data_type.time_zone {
+            result.push_str(&format!(" {}", time_zone));
+        }
+        for bound in &data_type.array_dimensions {
+            result.push_str(&format!("[{}]", bound.as_deref().unwrap_or("")));
+        }
+
+        result
+    }
+
+    // short CASE expressions stay on one line. longer ones put each WHEN and the
+    // ELSE on its own line a level in, with the THENs lined up, and END back
+    // at the current level
+    fn format_case(
+        &mut self,
+        operand: Option<&Expression>,
+        conditions: &[WhenClause],
+        else_result: Option<&Expression>,
+    ) -> String {
+        self.indent_level += 1;
+        let inner = self.indent();
+        let operand = operand.map(|operand| self.format_expression(operand));
+        let whens: Vec<(String, String)> = conditions
+            .iter()
+            .map(|when| {
+                (
+                    self.format_expression(&when.condition),
+                    self.format_expression(&when.result),
+                )
+            })
+            .collect();
+        let else_result = else_result.map(|result| self.format_expression(result));
+        self.indent_level -= 1;
+
+        let case = match &operand {
+            Some(operand) => format!("CASE {}", operand),
+            None => "CASE".to_string(),
+        };
+
+        let mut inline = case.clone();
+        for (condition, result) in &whens {
+            inline.push_str(&format!(" WHEN {} THEN {}", condition, result));
+        }
+        if let Some(result) = &else_result {
+            inline.push_str(&format!(" ELSE {}", result));
+        }
+        inline.push_str(" END");
+
+        if !inline.contains('\n')
+            && self.indent().len() + inline.len() <= self.config.max_line_length
+        {
+            return inline;
+        }
+
+        let width = whens
+            .iter()
+            .filter(|(condition, _)| !condition.contains('\n'))
+            .map(|(condition, _)| condition.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = vec![case];
+        for (condition, result) in &whens {
+            lines.push(format!(
+                "{}WHEN {:<width$} THEN {}",
+                inner,
+                condition,
+                result,
+                width = width
+            ));
+        }
+        if let Some(result) = &else_result {
+            lines.push(format!("{}ELSE {}", inner, result));
+        }
+        lines.push(format!("{}END", self.indent()));
+
+        lines.join("\n")
+    }
+
+    // a window specification in parentheses. it stays on one line if it fits
+    // after `offset` characters of the current line, otherwise each part goes
+    // on its own line a level in, like a subquery
+    fn format_window_spec(&mut self, spec: &WindowSpec, offset: usize) -> String {
+        let mut clauses = Vec::new();
+
+        if let Some(base) = &spec.base {
+            clauses.push(base.to_string());
+        }
+        if !spec.partition_by.is_empty() {
+            let expressions: Vec<String> = spec
+                .partition_by
+                .iter()
+                .map(|expr| self.format_expression(expr))
+                .collect();
+            clauses.push(format!("PARTITION BY {}", expressions.join(", ")));
+        }
+        if !spec.order_by.is_empty() {
+            clauses.push(format!(
+                "ORDER BY {}",
+                self.format_order_by_list(&spec.order_by)
+            ));
+        }
+        if let Some(frame) = &spec.frame {
+            let start = self.format_frame_bound(&frame.start);
+            clauses.push(match &frame.end {
+                Some(end) => format!(
+                    "{} BETWEEN {} AND {}",
+                    frame.units,
+                    start,
+                    self.format_frame_bound(end)
+                ),
+                None => format!("{} {}", frame.units, start),
+            });
+        }
+
+        let inline = format!("({})", clauses.join(" "));
+        if self.indent().len() + offset + inline.len() <= self.config.max_line_length {
+            return inline;
+        }
+
+        self.indent_level += 1;
+        let inner = self.indent();
+        self.indent_level -= 1;
+
+        let lines: Vec<String> = clauses
+            .iter()
+            .map(|clause| format!("{}{}", inner, clause))
+            .collect();
+        format!("(\n{}\n{})", lines.join("\n"), self.indent())
+    }
+
+    fn format_frame_bound(&mut self, bound: &FrameBound) -> String {
+        match bound {
+            FrameBound::UnboundedPreceding => "UNBOUNDED PRECEDING".to_string(),
+            FrameBound::Preceding(offset) => {
+                format!("{} PRECEDING", self.format_expression(offset))
+            }
+            FrameBound::CurrentRow => "CURRENT ROW".to_string(),
+            FrameBound::Following(offset) => {
+                format!("{} FOLLOWING", self.format_expression(offset))
+            }
+            FrameBound::UnboundedFollowing => "UNBOUNDED FOLLOWING".to_string(),
+        }
+    }
+
+    fn format_order_by_list(&mut self, items: &[OrderByItem]) -> String {
+        let items: Vec<String> = items
+            .iter()
+            .map(|item| self.format_order_by_item(item))
+            .collect();
+
+        items.join(", ")
+    }
+
+    fn format_order_by_item(&mut self, item: &OrderByItem) -> String {
+        let mut result = self.format_expression(&item.expr);
+
+        if let Some(direction) = &item.direction {
+            result.push_str(&format!(" {}", direction));
+        }
+        if let Some(nulls) = &item.nulls {
+            result.push_str(&format!(" {}", nulls));
+        }
+
+        result
+    }
+
+    // lay out a condition with each top-level AND/OR on its own line, the
+    // operator right-aligned with the clause `keyword` (e.g. WHERE) that
+    // starts at `indent`. an operator longer than the keyword hangs left of it
+    fn format_condition(&mut self, indent: &str, keyword: &str, expr: &Expression) -> String {
+        match expr {
+            Expression::BinaryOperation {
+                left,
+                op: op @ (Operator::And | Operator::Or),
+                right,
+                ..
+            } => {
+                let op = op.to_string();
+                let overhang = op.len().saturating_sub(keyword.len());
+                let op_indent = &indent[..indent.len().saturating_sub(overhang)];
 
-        if let Some(schema) = &table.schema {
-            result.push_str(schema);
-            result.push('.');
+                let mut lines: Vec<String> = self
+                    .format_condition(indent, keyword, left)
+                    .lines()
+                    .map(String::from)
+                    .collect();
+                // comments between the operands stay ahead of the operator
+                self.flush_comments(right.span().start, &mut lines);
+                lines.push(format!(
+                    "{}{:>width$} {}",
+                    op_indent,
+                    op,
+                    self.format_expression(right),
+                    width = keyword.len()
+                ));
+
+                lines.join("\n")
+            }
+            _ => self.format_expression(expr),
+        }
+    }
+
+    // a FROM list, or the tables an UPDATE targets, after `keyword`. later
+    // items and joins line up under the first table
+    fn format_tables(&mut self, keyword: &str, from: &[TableExpression], parts: &mut Vec<String>) {
+        let item_indent = format!("{}{}", self.indent(), " ".repeat(keyword.len()));
+
+        for (index, item) in from.iter().enumerate() {
+            // close off the previous item before any comments that follow it
+            if index > 0 {
+                if let Some(previous) = parts.last_mut() {
+                    previous.push(',');
+                }
+            }
+            self.flush_comments(item.span().start, parts);
+
+            let prefix = if index == 0 {
+                format!("{}{}", self.indent(), keyword)
+            } else {
+                item_indent.clone()
+            };
+            self.format_table_expression(item, &prefix, &item_indent, parts);
+        }
+    }
+
+    fn format_where(&mut self, where_clause: &WhereClause, parts: &mut Vec<String>) {
+        self.flush_comments(where_clause.span.start, parts);
+        let indent = self.indent();
+        self.indent_level += 1;
+        let condition = self.format_condition(&indent, "WHERE", &where_clause.condition);
+        self.indent_level -= 1;
+        parts.push(format!("{}WHERE {}", indent, condition));
+    }
+
+    // push the lines for `table`, the first one starting with `prefix`. each
+    // join goes on its own line at `join_indent`, with ON / USING below it
+    // right-aligned to the end of the join keyword
+    fn format_table_expression(
+        &mut self,
+        table: &TableExpression,
+        prefix: &str,
+        join_indent: &str,
+        lines: &mut Vec<String>,
+    ) {
+        match table {
+            TableExpression::Table(table) => {
+                let table = self.format_table_reference(table);
+                lines.push(format!("{}{}", prefix, table));
+            }
+            TableExpression::Join(join) => {
+                self.format_table_expression(&join.left, prefix, join_indent, lines);
+                self.flush_comments(join.right.span().start, lines);
+
+                let keyword = if join.natural {
+                    format!("NATURAL {}", join.kind)
+                } else {
+                    join.kind.to_string()
+                };
+                let join_prefix = format!("{}{} ", join_indent, keyword);
+                self.format_table_expression(&join.right, &join_prefix, join_indent, lines);
+
+                match &join.constraint {
+                    Some(JoinConstraint::On(condition)) => {
+                        let indent = format!(
+                            "{}{}",
+                            join_indent,
+                            " ".repeat(keyword.len().saturating_sub(2))
+                        );
+                        self.indent_level += 1;
+                        let condition = self.format_condition(&indent, "ON", condition);
+                        self.indent_level -= 1;
+                        lines.push(format!("{}ON {}", indent, condition));
+                    }
+                    Some(JoinConstraint::Using(columns)) => {
+                        let columns: Vec<String> =
+                            columns.iter().map(|column| column.to_string()).collect();
+                        lines.push(format!(
+                            "{}{}USING ({})",
+                            join_indent,
+                            " ".repeat(keyword.len().saturating_sub(5)),
+                            columns.join(", ")
+                        ));
+                    }
+                    None => {}
+                }
+            }
+            TableExpression::Derived { query, alias, .. } => {
+                self.indent_level += 1;
+                let mut derived = self.format_subquery(query);
+                self.indent_level -= 1;
+
+                if let Some(alias) = alias {
+                    derived.push_str(&format!(" AS {}", alias));
+                }
+                lines.push(format!("{}{}", prefix, derived));
+            }
+            // laid out like a derived table, the joins lined up under the
+            // first table inside the parens
+            TableExpression::Nested { expr, .. } => {
+                lines.push(format!("{}(", prefix));
+                self.indent_level += 2;
+                let inner = self.indent();
+                self.format_table_expression(expr, &inner, &inner, lines);
+                self.indent_level -= 1;
+                lines.push(format!("{})", self.indent()));
+                self.indent_level -= 1;
+            }
         }
+    }
 
-        result.push_str(&table.name);
+    fn format_table_reference(&mut self, table: &TableReference) -> String {
+        let mut result = table.name.to_string();
 
         if let Some(alias) = &table.alias {
             result.push_str(&format!(" AS {}", alias));
@@ -1,3 +1,5 @@
+use crate::config::SqlDialect;
+use crate::error::LexError;
 use logos::Logos;
 use std::ops::Range;
 
@@ -8,19 +10,72 @@ pub struct Token {
     pub span: Range<usize>,
 }
 
-// all t-sql tokens
+// tokens for t-sql, postgres, mysql and generic sql, trivia (whitespace and
+// comments) included so no input is lost
 #[derive(Logos, Debug, Clone, PartialEq)]
-#[logos(skip r"[ \t\n\f+]")] // skip whitespace
+#[logos(error = LexError)]
 pub enum TokenKind {
-    // keywords
+    // trivia - kept so the original text can be reproduced byte for byte
+    #[regex(r"[ \t\r\n\f]+")]
+    Whitespace,
+    #[regex(r"--[^\n\r]*")]
+    LineComment,
+    #[token("/*", block_comment)]
+    BlockComment,
+
+    // reserved keywords. every other keyword lexes as an identifier and the
+    // parser matches it by word where a clause expects it, so it can still
+    // name a column or table
+    #[token("INSERT", ignore(ascii_case))]
+    Insert,
+    #[token("DEFAULT", ignore(ascii_case))]
+    Default,
+    #[token("UPDATE", ignore(ascii_case))]
+    Update,
+    #[token("DELETE", ignore(ascii_case))]
+    Delete,
+    #[token("CREATE", ignore(ascii_case))]
+    Create,
+    #[token("TABLE", ignore(ascii_case))]
+    Table,
+    #[token("ALTER", ignore(ascii_case))]
+    Alter,
+    #[token("DROP", ignore(ascii_case))]
+    Drop,
+    #[token("WITH", ignore(ascii_case))]
+    With,
     #[token("SELECT", ignore(ascii_case))]
     Select,
+    #[token("UNION", ignore(ascii_case))]
+    Union,
+    #[token("INTERSECT", ignore(ascii_case))]
+    Intersect,
+    #[token("EXCEPT", ignore(ascii_case))]
+    Except,
+    #[token("ALL", ignore(ascii_case))]
+    All,
+    #[token("DISTINCT", ignore(ascii_case))]
+    Distinct,
     #[token("FROM", ignore(ascii_case))]
     From,
     #[token("WHERE", ignore(ascii_case))]
     Where,
     #[token("JOIN", ignore(ascii_case))]
     Join,
+    #[token("INNER", ignore(ascii_case))]
+    Inner,
+    #[token("LEFT", ignore(ascii_case))]
+    Left,
+    #[token("RIGHT", ignore(ascii_case))]
+    Right,
+    #[token("FULL", ignore(ascii_case))]
+    Full,
+    #[token("OUTER", ignore(ascii_case))]
+    Outer,
+    #[token("CROSS", ignore(ascii_case))]
+    Cross,
+    #[token("NATURAL", ignore(ascii_case))]
+    Natural,
     #[token("ON", ignore(ascii_case))]
     On,
     #[token("GROUP", ignore(ascii_case))]
@@ -33,6 +88,42 @@ pub enum TokenKind {
     Having,
     #[token("AS", ignore(ascii_case))]
     As,
+    #[token("BETWEEN", ignore(ascii_case))]
+    Between,
+    #[token("AND", ignore(ascii_case))]
+    And,
+    #[token("OR", ignore(ascii_case))]
+    Or,
+    #[token("NOT", ignore(ascii_case))]
+    Not,
+    #[token("IN", ignore(ascii_case))]
+    In,
+    #[token("EXISTS", ignore(ascii_case))]
+    Exists,
+    #[token("IS", ignore(ascii_case))]
+    Is,
+    #[token("LIKE", ignore(ascii_case))]
+    Like,
+    #[token("ILIKE", ignore(ascii_case))]
+    ILike,
+    #[token("CAST", ignore(ascii_case))]
+    Cast,
+    #[token("TRY_CAST", ignore(ascii_case))]
+    TryCast,
+    #[token("CONVERT", ignore(ascii_case))]
+    Convert,
+    #[token("TRY_CONVERT", ignore(ascii_case))]
+    TryConvert,
+    #[token("CASE", ignore(ascii_case))]
+    Case,
+    #[token("WHEN", ignore(ascii_case))]
+    When,
+    #[token("THEN", ignore(ascii_case))]
+    Then,
+    #[token("ELSE", ignore(ascii_case))]
+    Else,
+    #[token("END", ignore(ascii_case))]
+    End,
 
     // asterisk
     #[token("*")]
@@ -47,61 +138,295 @@ pub enum TokenKind {
     GreaterThan,
     #[token("<=")]
     LessEquals,
-    #[token("=>")]
+    #[token(">=")]
     GreaterEquals,
     #[token("<>")]
     NotEquals,
+    #[token("!=")]
+    BangEquals,
+    #[token("!<")]
+    NotLessThan,
+    #[token("!>")]
+    NotGreaterThan,
+    #[token("+")]
+    Plus,
+    #[token("-")]
+    Minus,
+    #[token("/")]
+    Slash,
+    #[token("%")]
+    Percent,
+    #[token("||")]
+    Concat,
+    #[token("&")]
+    Ampersand,
+    #[token("|")]
+    Pipe,
+    #[token("^")]
+    Caret,
+    #[token("~")]
+    Tilde,
+    #[token("<<")]
+    ShiftLeft,
+    #[token(">>")]
+    ShiftRight,
+    #[token("::")]
+    DoubleColon,
+    #[token("->")]
+    Arrow,
+    #[token("->>")]
+    LongArrow,
+    #[token("@>")]
+    AtArrow,
+    #[token("<@")]
+    ArrowAt,
+
+    // t-sql compound assignment
+    #[token("+=")]
+    PlusEquals,
+    #[token("-=")]
+    MinusEquals,
+    #[token("*=")]
+    AsteriskEquals,
+    #[token("/=")]
+    SlashEquals,
+    #[token("%=")]
+    PercentEquals,
+    #[token("&=")]
+    AmpersandEquals,
+    #[token("|=")]
+    PipeEquals,
+    #[token("^=")]
+    CaretEquals,
 
     // punctuation
     #[token(",")]
     Comma,
+    #[token(";")]
+    Semicolon,
     #[token(".")]
     Dot,
     #[token("(")]
     LParens,
     #[token(")")]
     RParens,
+    // only produced where `[` doesn't quote identifiers, see `next_token`
+    LBracket,
+    #[token("]")]
+    RBracket,
 
     // literals
-    #[regex("[0-9]+")]
+    #[token("NULL", ignore(ascii_case))]
+    Null,
+    #[token("TRUE", ignore(ascii_case))]
+    True,
+    #[token("FALSE", ignore(ascii_case))]
+    False,
+    #[regex(r"[0-9]+(\.[0-9]*)?([eE][+-]?[0-9]+)?")]
+    #[regex(r"\.[0-9]+([eE][+-]?[0-9]+)?")]
     Number,
-    #[regex(r#"'[^']*'"#)]
+    #[regex(r"0[xX][0-9a-fA-F]+")]
+    #[regex(r"[xX]'[0-9a-fA-F]*'")]
+    HexNumber,
+    #[regex(r"0[bB][01]+")]
+    #[regex(r"[bB]'[01]*'")]
+    BinaryNumber,
+    // plain, N'national' and E'escape' strings
+    #[token("'", string_literal)]
+    #[regex("[nNeE]'", string_literal)]
     String,
 
-    // identifiers - including quoted identifiers
-    #[regex(r#"[a-zA-Z_][a-zA-Z0-9_]*|\[[^\]]*\]"#)]
+    // identifiers - including "double", `backtick` and [bracket] quoted identifiers
+    #[regex(r#"[a-zA-Z_][a-zA-Z0-9_]*"#)]
+    #[token("$action", ignore(ascii_case))] // t-sql MERGE ... OUTPUT
+    #[token("\"", |lex| close_quoted(lex, '"', false, LexError::UnterminatedIdentifier))]
+    #[token("`", |lex| close_quoted(lex, '`', false, LexError::UnterminatedIdentifier))]
+    #[token("[", |lex| close_quoted(lex, ']', false, LexError::UnterminatedIdentifier))]
     Identifier,
+
+    // input the lexer couldn't make sense of. never produced by logos itself;
+    // the lexer records why in its diagnostics
+    Error,
+}
+
+// consume the rest of a string literal. E'...' strings also allow backslash escapes
+fn string_literal(lex: &mut logos::Lexer<TokenKind>) -> Result<(), LexError> {
+    let backslash_escapes = lex.slice().starts_with(['e', 'E']);
+    close_quoted(lex, '\'', backslash_escapes, LexError::UnterminatedString)
+}
+
+// consume input up to and including the closing `quote`. a doubled closing
+// quote is an escaped one and doesn't end the token.
+fn close_quoted(
+    lex: &mut logos::Lexer<TokenKind>,
+    quote: char,
+    backslash_escapes: bool,
+    error: LexError,
+) -> Result<(), LexError> {
+    let mut chars = lex.remainder().char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' && backslash_escapes {
+            chars.next();
+        } else if c == quote {
+            if chars.peek().is_some_and(|&(_, next)| next == quote) {
+                chars.next();
+            } else {
+                lex.bump(index + c.len_utf8());
+                return Ok(());
+            }
+        }
+    }
+
+    lex.bump(lex.remainder().len());
+    Err(error)
+}
+
+// consume the rest of a block comment up to and including the closing `*/`
+fn block_comment(lex: &mut logos::Lexer<TokenKind>) -> Result<(), LexError> {
+    close_delimited(lex, "*/", LexError::UnterminatedComment)
+}
+
+// consume input up to and including `close`. if it never shows up, the rest of
+// the input is swallowed into the error token so lexing doesn't cascade.
+fn close_delimited(
+    lex: &mut logos::Lexer<TokenKind>,
+    close: &str,
+    error: LexError,
+) -> Result<(), LexError> {
+    match lex.remainder().find(close) {
+        Some(end) => {
+            lex.bump(end + close.len());
+            Ok(())
+        }
+        None => {
+            lex.bump(lex.remainder().len());
+            Err(error)
+        }
+    }
+}
+
+impl TokenKind {
+    // trivia can appear between any two tokens and carries no syntax
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    }
+}
+
+// a problem found while lexing, pointing at the offending input
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub error: LexError,
+    pub span: Range<usize>,
 }
 
 // main lexer type for tokenizing sql input
 pub struct Lexer<'a> {
     logos_lexer: logos::Lexer<'a, TokenKind>,
+    diagnostics: Vec<Diagnostic>,
+    dialect: SqlDialect,
+    previous: Option<TokenKind>, // kind of the last token returned, trivia included
 }
 
 impl<'a> Lexer<'a> {
     // create a new lexer from input text
     pub fn new(input: &'a str) -> Self {
+        Self::with_dialect(input, SqlDialect::Generic)
+    }
+
+    // create a lexer that follows the quoting rules of `dialect`
+    pub fn with_dialect(input: &'a str, dialect: SqlDialect) -> Self {
         Self {
             logos_lexer: TokenKind::lexer(input),
+            diagnostics: Vec::new(),
+            dialect,
+            previous: None,
         }
     }
 
-    // get the next token from input
+    // get the next token from input. unrecognized input comes back as an
+    // `Error` token and is recorded in the diagnostics
     pub fn next_token(&mut self) -> Option<Token> {
-        let kind = Result::expect(self.logos_lexer.next()?, "failed to identify token.");
+        let result = match self.logos_lexer.next()? {
+            Ok(TokenKind::Identifier) => self.check_quoting(self.logos_lexer.slice()),
+            result => result,
+        };
         let span = self.logos_lexer.span();
 
+        // a `[` opening an array bound or subscript isn't a quote. restart
+        // lexing right after it
+        if self.logos_lexer.slice().starts_with('[') && self.opens_subscript() {
+            let mut lexer = TokenKind::lexer(self.logos_lexer.source());
+            lexer.bump(span.start + 1);
+            self.logos_lexer = lexer;
+            self.previous = Some(TokenKind::LBracket);
+
+            return Some(Token {
+                kind: TokenKind::LBracket,
+                span: span.start..span.start + 1,
+            });
+        }
+
+        let kind = result.unwrap_or_else(|error| {
+            self.diagnostics.push(Diagnostic {
+                error,
+                span: span.clone(),
+            });
+            TokenKind::Error
+        });
+        self.previous = Some(kind.clone());
+
         Some(Token { kind, span })
     }
 
+    // whether a `[` here starts a subscript or array bound. postgres has no
+    // bracket quoting at all, and t-sql no arrays. elsewhere it's one when it
+    // comes straight after a name or a closing paren or bracket, e.g. `int[]`,
+    // `varchar(10)[]` or `arr[1]`
+    fn opens_subscript(&self) -> bool {
+        match self.dialect {
+            SqlDialect::PostgreSql => true,
+            SqlDialect::TSql => false,
+            _ => matches!(
+                self.previous,
+                Some(TokenKind::Identifier | TokenKind::RParens | TokenKind::RBracket)
+            ),
+        }
+    }
+
     // peek at the next token without consuming it
     pub fn peek_token(&mut self) -> Option<Token> {
-        let _ = self.logos_lexer.span();
         let current_state = self.logos_lexer.clone();
+        let diagnostic_count = self.diagnostics.len();
+        let previous = self.previous.clone();
 
         let token = self.next_token();
         self.logos_lexer = current_state;
+        self.diagnostics.truncate(diagnostic_count);
+        self.previous = previous;
 
         token
     }
+
+    // every quoted identifier is lexed the same way, then checked against the
+    // quoting the dialect allows. MySQL treats "double quotes" as a string.
+    fn check_quoting(&self, text: &str) -> Result<TokenKind, LexError> {
+        match (text.chars().next(), self.dialect) {
+            (Some('"'), SqlDialect::MySql) => Ok(TokenKind::String),
+            // postgres never gets here with `[`, it always opens a subscript
+            (Some('`'), SqlDialect::TSql | SqlDialect::PostgreSql)
+            | (Some('['), SqlDialect::MySql) => Err(LexError::UnsupportedQuoting),
+            // a quoted name can't be empty, so `[]` is never an alias
+            (Some('"' | '`' | '['), _) if text.len() == 2 => Err(LexError::EmptyIdentifier),
+            _ => Ok(TokenKind::Identifier),
+        }
+    }
+
+    // lexical problems found so far
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
 }
@@ -4,6 +4,7 @@ pub mod error;
 pub mod formatter;
 pub mod lexer;
 pub mod parser;
+pub mod syntax;
 
 pub use config::{Config, LineBreakStyle, SqlDialect};
 pub use formatter::format_sql;
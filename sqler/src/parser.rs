@@ -1,114 +1,1307 @@
 use crate::ast::*;
+use crate::config::SqlDialect;
 use crate::error::ParseError;
-use crate::lexer::{Lexer, Token, TokenKind};
+use crate::lexer::{Diagnostic, Token, TokenKind};
+use crate::syntax::SyntaxTree;
 use std::ops::Range;
 
 pub struct Parser<'a> {
     input: &'a str,
-    lexer: Lexer<'a>,
+    tokens: std::vec::IntoIter<Token>,
     current: Option<Token>,
     peek: Option<Token>,
+    comments: Vec<Comment>,
+    line_break: bool,    // whether a line break came after the last token read
+    previous_end: usize, // end of the last token consumed
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(input: &'a str) -> Self {
-        let mut lexer = Lexer::new(input);
-        let current = lexer.next_token();
-        let peek = lexer.next_token();
+        Self::from_syntax_tree(&SyntaxTree::parse(input))
+    }
 
-        Self {
-            input,
-            lexer,
-            current,
-            peek,
-        }
+    pub fn with_dialect(input: &'a str, dialect: SqlDialect) -> Self {
+        Self::from_syntax_tree(&SyntaxTree::parse_with_dialect(input, dialect))
+    }
+
+    // parse the tokens of an existing syntax tree. spans in the AST index into
+    // the tree's input, see `SyntaxTree::covering_node`
+    pub fn from_syntax_tree(tree: &SyntaxTree<'a>) -> Self {
+        let tokens: Vec<Token> = tree.tokens().into_iter().cloned().collect();
+
+        let mut parser = Self {
+            input: tree.input(),
+            tokens: tokens.into_iter(),
+            current: None,
+            peek: None,
+            comments: Vec::new(),
+            line_break: true,
+            previous_end: 0,
+            diagnostics: tree.diagnostics().to_vec(),
+        };
+        parser.current = parser.next_significant_token();
+        parser.peek = parser.next_significant_token();
+
+        parser
     }
 
     fn advance(&mut self) -> Option<Token> {
-        let next = self.lexer.next_token();
+        if let Some(ref token) = self.current {
+            self.previous_end = token.span.end;
+        }
+
+        let next = self.next_significant_token();
         let current = self.peek.take();
         self.peek = next;
         std::mem::replace(&mut self.current, current)
     }
 
+    // pull the next non-trivia token, setting aside any comments in front of it
+    fn next_significant_token(&mut self) -> Option<Token> {
+        loop {
+            let token = self.tokens.next()?;
+
+            let kind = match token.kind {
+                TokenKind::Whitespace => {
+                    self.line_break |= self.input[token.span].contains('\n');
+                    continue;
+                }
+                TokenKind::LineComment => CommentKind::Line,
+                TokenKind::BlockComment => CommentKind::Block,
+                _ => {
+                    self.line_break = false;
+                    return Some(token);
+                }
+            };
+
+            // a comment is on its own line if a line break separates it from
+            // whatever came before it
+            self.comments.push(Comment {
+                span: token.span.clone().into(),
+                text: self.input[token.span].trim_end().to_string(),
+                kind,
+                own_line: self.line_break,
+            });
+            self.line_break = false;
+        }
+    }
+
+    // take the comments that appear before the current token
+    fn take_comments(&mut self) -> Vec<Comment> {
+        let end = match self.current {
+            Some(ref token) => token.span.start,
+            None => self.input.len(),
+        };
+        let split = self
+            .comments
+            .iter()
+            .position(|comment| comment.span.start >= end)
+            .unwrap_or(self.comments.len());
+
+        self.comments.drain(..split).collect()
+    }
+
     fn expect(&mut self, kind: TokenKind) -> Result<Token, ParseError> {
         match self.current {
             Some(ref token) if token.kind == kind => Ok(self.advance().unwrap()),
-            Some(ref token) => Err(ParseError::UnexpectedToken {
-                expected: kind,
+            _ => Err(self.unexpected(kind)),
+        }
+    }
+
+    fn check(&self, kind: TokenKind) -> bool {
+        matches!(self.current, Some(ref token) if token.kind == kind)
+    }
+
+    // consume the current token only if it's of the given kind
+    fn consume(&mut self, kind: TokenKind) -> Option<Token> {
+        if self.check(kind) {
+            self.advance()
+        } else {
+            None
+        }
+    }
+
+    // the error for finding the current token where `expected` should be. a
+    // lexical error in the way is reported as such rather than as a bad token
+    fn unexpected(&self, expected: TokenKind) -> ParseError {
+        match self.current {
+            Some(Token {
+                kind: TokenKind::Error,
+                ref span,
+            }) => ParseError::Lexical {
+                error: self
+                    .diagnostics
+                    .iter()
+                    .find(|diagnostic| diagnostic.span == *span)
+                    .map(|diagnostic| diagnostic.error.clone())
+                    .unwrap_or_default(),
+                span: span.clone(),
+            },
+            Some(ref token) => ParseError::UnexpectedToken {
+                expected,
                 found: token.kind.clone(),
                 span: token.span.clone(),
-            }),
-            None => Err(ParseError::UnexpectedEOF),
+            },
+            None => ParseError::UnexpectedEOF,
         }
     }
 
-    pub fn parse_select(&mut self) -> Result<SelectStatement, ParseError> {
-        let start_span = self.expect(TokenKind::Select)?.span;
+    // lexical problems in the input being parsed
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
 
-        // parse columns
-        let mut columns = Vec::new();
-        loop {
-            columns.push(self.parse_select_item()?);
+    // comments no statement has taken, e.g. all of them when there are none
+    pub fn take_remaining_comments(&mut self) -> Vec<Comment> {
+        std::mem::take(&mut self.comments)
+    }
 
-            if !matches!(
-                self.current,
+    // a full query: CTEs, SELECTs combined by set operators, then the ordering
+    // and row limits that apply to the whole result
+    pub fn parse_query(&mut self) -> Result<Query, ParseError> {
+        let start = match self.current {
+            Some(ref token) => token.span.start,
+            None => return Err(ParseError::UnexpectedEOF),
+        };
+
+        let with = if self.check(TokenKind::With) {
+            Some(self.parse_with()?)
+        } else {
+            None
+        };
+
+        let body = self.parse_set_expression(0)?;
+
+        // parse ORDER BY clause
+        let order_by = if self.consume(TokenKind::Order).is_some() {
+            self.expect(TokenKind::By)?;
+            Some(self.parse_order_by_list()?)
+        } else {
+            None
+        };
+
+        // LIMIT and OFFSET may come in either order, FETCH always goes last
+        let mut limit = self.parse_limit()?;
+        let offset = self.parse_offset()?;
+        if limit.is_none() {
+            limit = self.parse_limit()?;
+        }
+        let fetch = self.parse_fetch()?;
+
+        Ok(Query {
+            span: Span::from(start..self.previous_end),
+            with,
+            body,
+            order_by,
+            limit,
+            offset,
+            fetch,
+            comments: self.take_comments(),
+        })
+    }
+
+    // operands joined by UNION, INTERSECT and EXCEPT. INTERSECT binds tighter
+    fn parse_set_expression(&mut self, min_precedence: u8) -> Result<SetExpression, ParseError> {
+        let mut left = self.parse_set_operand()?;
+
+        while let Some(op) = self.current.as_ref().and_then(|t| set_operator(&t.kind)) {
+            let precedence = op.precedence();
+            if precedence <= min_precedence {
+                break;
+            }
+            self.advance(); // consume the operator
+
+            let quantifier = self.parse_quantifier();
+            let right = self.parse_set_expression(precedence)?;
+
+            left = SetExpression::Operation {
+                span: Span::from(left.span().start..right.span().end),
+                left: Box::new(left),
+                op,
+                quantifier,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(left)
+    }
+
+    // a SELECT, or a whole query in parentheses
+    fn parse_set_operand(&mut self) -> Result<SetExpression, ParseError> {
+        if self.consume(TokenKind::LParens).is_some() {
+            let query = self.parse_subquery_body()?;
+            return Ok(SetExpression::Query(Box::new(query)));
+        }
+
+        Ok(SetExpression::Select(Box::new(self.parse_select()?)))
+    }
+
+    fn parse_quantifier(&mut self) -> Option<Quantifier> {
+        if self.consume(TokenKind::All).is_some() {
+            Some(Quantifier::All)
+        } else if self.consume(TokenKind::Distinct).is_some() {
+            Some(Quantifier::Distinct)
+        } else {
+            None
+        }
+    }
+
+    // statements separated by semicolons, e.g. a whole migration file. t-sql
+    // doesn't need the semicolons, so they're optional. each statement comes
+    // with whether a semicolon ended it
+    pub fn parse_statements(&mut self) -> Result<Vec<(AST, bool)>, ParseError> {
+        let mut statements = Vec::new();
+
+        while self.consume(TokenKind::Semicolon).is_some() {}
+        while self.current.is_some() {
+            let mut statement = self.parse_statement()?;
+            let terminated = self.consume(TokenKind::Semicolon).is_some();
+            while self.consume(TokenKind::Semicolon).is_some() {}
+
+            // a comment on the same line as the semicolon still belongs to this
+            // statement, as does everything after the last one
+            let end = match self.current {
+                Some(ref token) => token.span.start,
+                None => self.input.len(),
+            };
+            let trailing = self.current.is_none();
+            let split = self
+                .comments
+                .iter()
+                .position(|comment| comment.span.start >= end || (comment.own_line && !trailing))
+                .unwrap_or(self.comments.len());
+            statement_comments(&mut statement).extend(self.comments.drain(..split));
+
+            statements.push((statement, terminated));
+        }
+
+        Ok(statements)
+    }
+
+    // a single statement of any kind
+    pub fn parse_statement(&mut self) -> Result<AST, ParseError> {
+        match self.current {
+            Some(Token {
+                kind: TokenKind::Insert,
+                ..
+            }) => Ok(AST::Insert(Box::new(self.parse_insert()?))),
+            Some(Token {
+                kind: TokenKind::Update,
+                ..
+            }) => Ok(AST::Update(Box::new(self.parse_update()?))),
+            Some(Token {
+                kind: TokenKind::Delete,
+                ..
+            }) => Ok(AST::Delete(Box::new(self.parse_delete()?))),
+            Some(Token {
+                kind: TokenKind::Create,
+                ..
+            }) => Ok(AST::CreateTable(Box::new(self.parse_create_table()?))),
+            Some(Token {
+                kind: TokenKind::Alter,
+                ..
+            }) => Ok(AST::AlterTable(Box::new(self.parse_alter_table()?))),
+            Some(Token {
+                kind: TokenKind::Drop,
+                ..
+            }) => Ok(AST::Drop(Box::new(self.parse_drop()?))),
+            Some(ref token) if self.is_word(token, "merge") => {
+                Ok(AST::Merge(Box::new(self.parse_merge()?)))
+            }
+            Some(ref token) if self.is_word(token, "truncate") => {
+                Ok(AST::Truncate(Box::new(self.parse_truncate()?)))
+            }
+            _ => Ok(AST::Query(Box::new(self.parse_query()?))),
+        }
+    }
+
+    // INSERT [INTO] table [(columns)] [OUTPUT ...]
+    // {VALUES (...), ... | query | DEFAULT VALUES} [upsert] [RETURNING ...]
+    pub fn parse_insert(&mut self) -> Result<InsertStatement, ParseError> {
+        let start = self.expect(TokenKind::Insert)?.span.start;
+        self.consume_word("into"); // optional in t-sql and mysql
+        let table = self.parse_object_name()?;
+
+        // a parenthesized query is the source, not a column list
+        let columns = if self.check(TokenKind::LParens)
+            && !matches!(
+                self.peek,
                 Some(Token {
-                    kind: TokenKind::Comma,
+                    kind: TokenKind::Select | TokenKind::With,
                     ..
                 })
             ) {
+            self.parse_identifier_list()?
+        } else {
+            Vec::new()
+        };
+
+        let mut returning = self.parse_output()?;
+
+        let source = if self.consume(TokenKind::Default).is_some() {
+            self.expect_word("values")?;
+            InsertSource::DefaultValues
+        } else if self.consume_word("values").is_some() {
+            InsertSource::Values(self.parse_value_rows()?)
+        } else {
+            let query_start = self.current.as_ref().map_or(0, |token| token.span.start);
+            let mut query = self.parse_query()?;
+            self.hand_back_comments(&mut query, query_start);
+            InsertSource::Query(Box::new(query))
+        };
+
+        let upsert = self.parse_upsert()?;
+
+        if returning.is_none() {
+            returning = self.parse_returning()?;
+        }
+
+        Ok(InsertStatement {
+            span: Span::from(start..self.previous_end),
+            table,
+            columns,
+            source,
+            upsert,
+            returning,
+            comments: self.take_comments(),
+        })
+    }
+
+    // the `(...), (...)` rows after VALUES
+    fn parse_value_rows(&mut self) -> Result<Vec<Vec<Expression>>, ParseError> {
+        let mut rows = Vec::new();
+        loop {
+            self.expect(TokenKind::LParens)?;
+            rows.push(self.parse_expression_list()?);
+            self.expect(TokenKind::RParens)?;
+
+            if self.consume(TokenKind::Comma).is_none() {
                 break;
             }
-            self.advance();
-        }
+        }
+
+        Ok(rows)
+    }
+
+    // UPDATE tables SET ... [OUTPUT ...] [FROM ...] [WHERE ...] [RETURNING ...]
+    pub fn parse_update(&mut self) -> Result<UpdateStatement, ParseError> {
+        let start = self.expect(TokenKind::Update)?.span.start;
+        let tables = self.parse_from_list()?;
+
+        self.expect_word("set")?;
+        let assignments = self.parse_assignments()?;
+
+        let mut returning = self.parse_output()?;
+
+        let from = if self.consume(TokenKind::From).is_some() {
+            Some(self.parse_from_list()?)
+        } else {
+            None
+        };
+
+        let where_clause = if self.consume(TokenKind::Where).is_some() {
+            Some(self.parse_where_clause()?)
+        } else {
+            None
+        };
+
+        if returning.is_none() {
+            returning = self.parse_returning()?;
+        }
+
+        Ok(UpdateStatement {
+            span: Span::from(start..self.previous_end),
+            tables,
+            assignments,
+            from,
+            where_clause,
+            returning,
+            comments: self.take_comments(),
+        })
+    }
+
+    // DELETE [TOP (n)] [FROM] tables [OUTPUT ...] [FROM ...] [USING ...]
+    // [WHERE ...] [RETURNING ...]
+    pub fn parse_delete(&mut self) -> Result<DeleteStatement, ParseError> {
+        let start = self.expect(TokenKind::Delete)?.span.start;
+
+        // t-sql requires the parentheses, so `top` alone can still be a table
+        let top = if matches!(
+            self.peek,
+            Some(Token {
+                kind: TokenKind::LParens,
+                ..
+            })
+        ) && self.consume_word("top").is_some()
+        {
+            self.expect(TokenKind::LParens)?;
+            let top = self.parse_expression()?;
+            self.expect(TokenKind::RParens)?;
+            Some(top)
+        } else {
+            None
+        };
+
+        self.consume(TokenKind::From);
+        let tables = self.parse_from_list()?;
+
+        let mut returning = self.parse_output()?;
+
+        let from = if self.consume(TokenKind::From).is_some() {
+            Some(self.parse_from_list()?)
+        } else {
+            None
+        };
+
+        let using = if self.consume_word("using").is_some() {
+            Some(self.parse_from_list()?)
+        } else {
+            None
+        };
+
+        let where_clause = if self.consume(TokenKind::Where).is_some() {
+            Some(self.parse_where_clause()?)
+        } else {
+            None
+        };
+
+        if returning.is_none() {
+            returning = self.parse_returning()?;
+        }
+
+        Ok(DeleteStatement {
+            span: Span::from(start..self.previous_end),
+            top,
+            tables,
+            from,
+            using,
+            where_clause,
+            returning,
+            comments: self.take_comments(),
+        })
+    }
+
+    // MERGE [INTO] target USING source ON condition WHEN ... [WHEN ...]
+    // [OUTPUT ... | RETURNING ...]
+    pub fn parse_merge(&mut self) -> Result<MergeStatement, ParseError> {
+        let start = self.expect_word("merge")?.span.start;
+        self.consume_word("into");
+        let target = self.parse_table_reference()?;
+
+        self.expect_word("using")?;
+        let source = self.parse_table_factor()?;
+        self.expect(TokenKind::On)?;
+        let on = self.parse_expression()?;
+
+        let mut clauses = vec![self.parse_merge_clause()?];
+        while self.check(TokenKind::When) {
+            clauses.push(self.parse_merge_clause()?);
+        }
+
+        let mut returning = self.parse_output()?;
+        if returning.is_none() {
+            returning = self.parse_returning()?;
+        }
+
+        Ok(MergeStatement {
+            span: Span::from(start..self.previous_end),
+            target,
+            source,
+            on,
+            clauses,
+            returning,
+            comments: self.take_comments(),
+        })
+    }
+
+    // WHEN [NOT] MATCHED [BY SOURCE | BY TARGET] [AND condition] THEN
+    // {UPDATE SET ... | DELETE | INSERT [(columns)] VALUES (...) | DO NOTHING}
+    fn parse_merge_clause(&mut self) -> Result<MergeClause, ParseError> {
+        let start = self.expect(TokenKind::When)?.span.start;
+
+        let kind = if self.consume(TokenKind::Not).is_some() {
+            self.expect_word("matched")?;
+            if self.consume(TokenKind::By).is_none() {
+                MergeMatch::NotMatched
+            } else if self.consume_word("source").is_some() {
+                MergeMatch::NotMatchedBySource
+            } else {
+                self.expect_word("target")?;
+                MergeMatch::NotMatchedByTarget
+            }
+        } else {
+            self.expect_word("matched")?;
+            MergeMatch::Matched
+        };
+
+        let condition = if self.consume(TokenKind::And).is_some() {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+        self.expect(TokenKind::Then)?;
+
+        let action = if self.consume(TokenKind::Update).is_some() {
+            self.expect_word("set")?;
+            MergeAction::Update(self.parse_assignments()?)
+        } else if self.consume(TokenKind::Delete).is_some() {
+            MergeAction::Delete
+        } else if self.consume(TokenKind::Insert).is_some() {
+            let columns = if self.check(TokenKind::LParens) {
+                self.parse_identifier_list()?
+            } else {
+                Vec::new()
+            };
+
+            let source = if self.consume(TokenKind::Default).is_some() {
+                self.expect_word("values")?;
+                InsertSource::DefaultValues
+            } else {
+                self.expect_word("values")?;
+                self.expect(TokenKind::LParens)?;
+                let row = self.parse_expression_list()?;
+                self.expect(TokenKind::RParens)?;
+                InsertSource::Values(vec![row])
+            };
+            MergeAction::Insert { columns, source }
+        } else {
+            self.expect_word("do")?;
+            self.expect_word("nothing")?;
+            MergeAction::DoNothing
+        };
+
+        Ok(MergeClause {
+            span: Span::from(start..self.previous_end),
+            kind,
+            condition,
+            action,
+        })
+    }
+
+    // CREATE [TEMP | TEMPORARY] TABLE [IF NOT EXISTS] name (element, ...)
+    pub fn parse_create_table(&mut self) -> Result<CreateTableStatement, ParseError> {
+        let start = self.expect(TokenKind::Create)?.span.start;
+        let temporary =
+            self.consume_word("temp").is_some() || self.consume_word("temporary").is_some();
+        self.expect(TokenKind::Table)?;
+
+        let if_not_exists = self.parse_if_not_exists()?;
+        let name = self.parse_object_name()?;
+
+        self.expect(TokenKind::LParens)?;
+        let mut elements = Vec::new();
+        loop {
+            elements.push(self.parse_table_element()?);
+            if self.consume(TokenKind::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect(TokenKind::RParens)?;
+
+        Ok(CreateTableStatement {
+            span: Span::from(start..self.previous_end),
+            temporary,
+            if_not_exists,
+            name,
+            elements,
+            comments: self.take_comments(),
+        })
+    }
+
+    // a column definition or a table constraint
+    fn parse_table_element(&mut self) -> Result<TableElement, ParseError> {
+        if self.at_table_constraint() {
+            return Ok(TableElement::Constraint(self.parse_table_constraint()?));
+        }
+        Ok(TableElement::Column(self.parse_column_definition()?))
+    }
+
+    // name type [constraint ...]
+    fn parse_column_definition(&mut self) -> Result<ColumnDefinition, ParseError> {
+        let start = match self.current {
+            Some(ref token) => token.span.start,
+            None => return Err(ParseError::UnexpectedEOF),
+        };
+
+        let name = self.parse_identifier()?;
+        let data_type = self.parse_data_type()?;
+        let mut constraints = Vec::new();
+        while let Some(constraint) = self.parse_column_constraint()? {
+            constraints.push(constraint);
+        }
+
+        Ok(ColumnDefinition {
+            span: Span::from(start..self.previous_end),
+            name,
+            data_type,
+            constraints,
+        })
+    }
+
+    fn parse_if_exists(&mut self) -> Result<bool, ParseError> {
+        if self.consume_word("if").is_none() {
+            return Ok(false);
+        }
+        self.expect(TokenKind::Exists)?;
+        Ok(true)
+    }
+
+    fn parse_if_not_exists(&mut self) -> Result<bool, ParseError> {
+        if self.consume_word("if").is_none() {
+            return Ok(false);
+        }
+        self.expect(TokenKind::Not)?;
+        self.expect(TokenKind::Exists)?;
+        Ok(true)
+    }
+
+    // ALTER TABLE [IF EXISTS] name action [, ...]
+    pub fn parse_alter_table(&mut self) -> Result<AlterTableStatement, ParseError> {
+        let start = self.expect(TokenKind::Alter)?.span.start;
+        self.expect(TokenKind::Table)?;
+        let if_exists = self.parse_if_exists()?;
+        let name = self.parse_object_name()?;
+
+        let mut actions = vec![self.parse_alter_table_action()?];
+        while self.consume(TokenKind::Comma).is_some() {
+            actions.push(self.parse_alter_table_action()?);
+        }
+
+        Ok(AlterTableStatement {
+            span: Span::from(start..self.previous_end),
+            if_exists,
+            name,
+            actions,
+            comments: self.take_comments(),
+        })
+    }
+
+    // ADD {[COLUMN] definition | constraint}, DROP {[COLUMN] name | CONSTRAINT name},
+    // ALTER [COLUMN] name change, or RENAME {[COLUMN] a TO b | TO name}
+    fn parse_alter_table_action(&mut self) -> Result<AlterTableAction, ParseError> {
+        let start = match self.current {
+            Some(ref token) => token.span.start,
+            None => return Err(ParseError::UnexpectedEOF),
+        };
+
+        let kind = if self.consume_word("add").is_some() {
+            if self.at_table_constraint() {
+                AlterTableActionKind::AddConstraint(self.parse_table_constraint()?)
+            } else {
+                let column_keyword = self.consume_word("column").is_some();
+                AlterTableActionKind::AddColumn {
+                    column_keyword,
+                    if_not_exists: self.parse_if_not_exists()?,
+                    column: self.parse_column_definition()?,
+                }
+            }
+        } else if self.consume(TokenKind::Drop).is_some() {
+            // DROP constraint alone drops a column of that name
+            let constraint = matches!(
+                self.peek,
+                Some(Token {
+                    kind: TokenKind::Identifier,
+                    ..
+                })
+            ) && self.consume_word("constraint").is_some();
+            if !constraint {
+                self.consume_word("column");
+            }
+            let if_exists = self.parse_if_exists()?;
+            let name = self.parse_identifier()?;
+            let behavior = self.parse_drop_behavior();
+
+            if constraint {
+                AlterTableActionKind::DropConstraint {
+                    if_exists,
+                    name,
+                    behavior,
+                }
+            } else {
+                AlterTableActionKind::DropColumn {
+                    if_exists,
+                    name,
+                    behavior,
+                }
+            }
+        } else if self.consume(TokenKind::Alter).is_some() {
+            self.consume_word("column");
+            AlterTableActionKind::AlterColumn {
+                name: self.parse_identifier()?,
+                change: self.parse_alter_column_change()?,
+            }
+        } else {
+            self.expect_word("rename")?;
+            if self.consume_word("to").is_some() {
+                AlterTableActionKind::RenameTable(self.parse_object_name()?)
+            } else {
+                self.consume_word("column");
+                let old = self.parse_identifier()?;
+                self.expect_word("to")?;
+                AlterTableActionKind::RenameColumn {
+                    old,
+                    new: self.parse_identifier()?,
+                }
+            }
+        };
+
+        Ok(AlterTableAction {
+            span: Span::from(start..self.previous_end),
+            kind,
+        })
+    }
+
+    // {SET DATA TYPE | TYPE} t, {SET | DROP} DEFAULT or {SET | DROP} NOT NULL
+    fn parse_alter_column_change(&mut self) -> Result<AlterColumnChange, ParseError> {
+        if self.consume_word("set").is_some() {
+            if self.consume(TokenKind::Default).is_some() {
+                Ok(AlterColumnChange::SetDefault(self.parse_expression()?))
+            } else if self.consume(TokenKind::Not).is_some() {
+                self.expect(TokenKind::Null)?;
+                Ok(AlterColumnChange::SetNotNull)
+            } else {
+                self.expect_word("data")?;
+                self.expect_word("type")?;
+                Ok(AlterColumnChange::SetDataType(self.parse_data_type()?))
+            }
+        } else if self.consume(TokenKind::Drop).is_some() {
+            if self.consume(TokenKind::Default).is_some() {
+                Ok(AlterColumnChange::DropDefault)
+            } else {
+                self.expect(TokenKind::Not)?;
+                self.expect(TokenKind::Null)?;
+                Ok(AlterColumnChange::DropNotNull)
+            }
+        } else {
+            self.expect_word("type")?;
+            Ok(AlterColumnChange::SetDataType(self.parse_data_type()?))
+        }
+    }
+
+    fn parse_drop_behavior(&mut self) -> Option<DropBehavior> {
+        if self.consume_word("cascade").is_some() {
+            Some(DropBehavior::Cascade)
+        } else if self.consume_word("restrict").is_some() {
+            Some(DropBehavior::Restrict)
+        } else {
+            None
+        }
+    }
+
+    // DROP {TABLE | VIEW | INDEX | SCHEMA} [IF EXISTS] name [, ...] [ON table]
+    // [CASCADE | RESTRICT]
+    pub fn parse_drop(&mut self) -> Result<DropStatement, ParseError> {
+        let start = self.expect(TokenKind::Drop)?.span.start;
+
+        let object_type = if self.consume(TokenKind::Table).is_some() {
+            ObjectType::Table
+        } else if self.consume_word("view").is_some() {
+            ObjectType::View
+        } else if self.consume_word("index").is_some() {
+            ObjectType::Index
+        } else if self.consume_word("schema").is_some() {
+            ObjectType::Schema
+        } else {
+            return Err(self.unexpected(TokenKind::Table));
+        };
+        let if_exists = self.parse_if_exists()?;
+
+        let mut names = vec![self.parse_object_name()?];
+        while self.consume(TokenKind::Comma).is_some() {
+            names.push(self.parse_object_name()?);
+        }
+
+        let table = if object_type == ObjectType::Index && self.consume(TokenKind::On).is_some() {
+            Some(self.parse_object_name()?)
+        } else {
+            None
+        };
+
+        Ok(DropStatement {
+            span: Span::from(start..self.previous_end),
+            object_type,
+            if_exists,
+            names,
+            table,
+            behavior: self.parse_drop_behavior(),
+            comments: self.take_comments(),
+        })
+    }
+
+    // TRUNCATE [TABLE] name [, ...] [RESTART IDENTITY] [CASCADE | RESTRICT]
+    pub fn parse_truncate(&mut self) -> Result<TruncateStatement, ParseError> {
+        let start = self.expect_word("truncate")?.span.start;
+        self.consume(TokenKind::Table);
+
+        let mut tables = vec![self.parse_object_name()?];
+        while self.consume(TokenKind::Comma).is_some() {
+            tables.push(self.parse_object_name()?);
+        }
+
+        let restart_identity = if self.consume_word("restart").is_some() {
+            self.expect_word("identity")?;
+            true
+        } else {
+            false
+        };
+
+        Ok(TruncateStatement {
+            span: Span::from(start..self.previous_end),
+            tables,
+            restart_identity,
+            behavior: self.parse_drop_behavior(),
+            comments: self.take_comments(),
+        })
+    }
+
+    // none of these words are reserved, so a column may be named `unique` as
+    // long as no paren follows it. `constraint` followed by a name always
+    // starts a constraint though, that column needs quoting
+    fn at_table_constraint(&self) -> bool {
+        let Some(ref token) = self.current else {
+            return false;
+        };
+        let next_is = |kind: TokenKind| matches!(self.peek, Some(ref next) if next.kind == kind);
+        let next_is_key = matches!(self.peek, Some(ref next) if self.is_word(next, "key"));
+
+        self.is_word(token, "constraint") && next_is(TokenKind::Identifier)
+            || (self.is_word(token, "primary") || self.is_word(token, "foreign")) && next_is_key
+            || (self.is_word(token, "unique") || self.is_word(token, "check"))
+                && next_is(TokenKind::LParens)
+    }
+
+    // [CONSTRAINT name] {PRIMARY KEY (columns) | UNIQUE (columns)
+    // | FOREIGN KEY (columns) REFERENCES ... | CHECK (condition)}
+    fn parse_table_constraint(&mut self) -> Result<TableConstraint, ParseError> {
+        let start = match self.current {
+            Some(ref token) => token.span.start,
+            None => return Err(ParseError::UnexpectedEOF),
+        };
+        let name = if self.consume_word("constraint").is_some() {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+
+        let kind = if self.consume_word("primary").is_some() {
+            self.expect_word("key")?;
+            TableConstraintKind::PrimaryKey(self.parse_identifier_list()?)
+        } else if self.consume_word("unique").is_some() {
+            TableConstraintKind::Unique(self.parse_identifier_list()?)
+        } else if self.consume_word("foreign").is_some() {
+            self.expect_word("key")?;
+            let columns = self.parse_identifier_list()?;
+            self.expect_word("references")?;
+            TableConstraintKind::ForeignKey {
+                columns,
+                references: self.parse_references()?,
+            }
+        } else {
+            self.expect_word("check")?;
+            TableConstraintKind::Check(self.parse_check_condition()?)
+        };
+
+        Ok(TableConstraint {
+            span: Span::from(start..self.previous_end),
+            name,
+            kind,
+        })
+    }
+
+    // one constraint after a column's type, or None at the end of the column
+    fn parse_column_constraint(&mut self) -> Result<Option<ColumnConstraint>, ParseError> {
+        let start = match self.current {
+            Some(ref token) => token.span.start,
+            None => return Ok(None),
+        };
+        let name = if self.consume_word("constraint").is_some() {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+
+        let kind = if self.consume(TokenKind::Not).is_some() {
+            self.expect(TokenKind::Null)?;
+            ColumnConstraintKind::NotNull
+        } else if self.consume(TokenKind::Null).is_some() {
+            ColumnConstraintKind::Null
+        } else if self.consume(TokenKind::Default).is_some() {
+            ColumnConstraintKind::Default(self.parse_expression()?)
+        } else if self.consume_word("primary").is_some() {
+            self.expect_word("key")?;
+            ColumnConstraintKind::PrimaryKey
+        } else if self.consume_word("unique").is_some() {
+            ColumnConstraintKind::Unique
+        } else if self.consume_word("references").is_some() {
+            ColumnConstraintKind::References(self.parse_references()?)
+        } else if self.consume_word("check").is_some() {
+            ColumnConstraintKind::Check(self.parse_check_condition()?)
+        } else if self.consume_word("collate").is_some() {
+            ColumnConstraintKind::Collate(self.parse_identifier()?)
+        } else if self.consume_word("auto_increment").is_some() {
+            ColumnConstraintKind::AutoIncrement
+        } else if self.consume_word("identity").is_some() {
+            let seed = if self.consume(TokenKind::LParens).is_some() {
+                let seed = self.parse_expression()?;
+                self.expect(TokenKind::Comma)?;
+                let increment = self.parse_expression()?;
+                self.expect(TokenKind::RParens)?;
+                Some((seed, increment))
+            } else {
+                None
+            };
+            ColumnConstraintKind::Identity(seed)
+        } else if self.consume_word("generated").is_some() {
+            self.parse_generated()?
+        } else if name.is_some() {
+            return Err(self.unexpected(TokenKind::Identifier));
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(ColumnConstraint {
+            span: Span::from(start..self.previous_end),
+            name,
+            kind,
+        }))
+    }
+
+    // the rest of GENERATED {ALWAYS | BY DEFAULT} AS {IDENTITY | (expr) [STORED]}
+    fn parse_generated(&mut self) -> Result<ColumnConstraintKind, ParseError> {
+        let always = if self.consume_word("always").is_some() {
+            true
+        } else {
+            self.expect(TokenKind::By)?;
+            self.expect(TokenKind::Default)?;
+            false
+        };
+        self.expect(TokenKind::As)?;
+
+        if self.consume_word("identity").is_some() {
+            return Ok(ColumnConstraintKind::GeneratedIdentity { always });
+        }
+        // only identity columns can be generated BY DEFAULT
+        if !always {
+            return Err(self.unexpected(TokenKind::Identifier));
+        }
+
+        self.expect(TokenKind::LParens)?;
+        let expr = self.parse_expression()?;
+        self.expect(TokenKind::RParens)?;
+        let stored = self.consume_word("stored").is_some();
+
+        Ok(ColumnConstraintKind::GeneratedAs { expr, stored })
+    }
+
+    // the rest of REFERENCES table [(columns)] [ON DELETE action] [ON UPDATE action]
+    fn parse_references(&mut self) -> Result<ForeignKeyReference, ParseError> {
+        let table = self.parse_object_name()?;
+        let columns = if self.check(TokenKind::LParens) {
+            self.parse_identifier_list()?
+        } else {
+            Vec::new()
+        };
+
+        let mut on_delete = None;
+        let mut on_update = None;
+        while self.consume(TokenKind::On).is_some() {
+            if self.consume(TokenKind::Delete).is_some() {
+                on_delete = Some(self.parse_referential_action()?);
+            } else {
+                self.expect(TokenKind::Update)?;
+                on_update = Some(self.parse_referential_action()?);
+            }
+        }
+
+        Ok(ForeignKeyReference {
+            table,
+            columns,
+            on_delete,
+            on_update,
+        })
+    }
+
+    // CASCADE | RESTRICT | NO ACTION | SET NULL | SET DEFAULT
+    fn parse_referential_action(&mut self) -> Result<ReferentialAction, ParseError> {
+        if self.consume_word("cascade").is_some() {
+            Ok(ReferentialAction::Cascade)
+        } else if self.consume_word("restrict").is_some() {
+            Ok(ReferentialAction::Restrict)
+        } else if self.consume_word("no").is_some() {
+            self.expect_word("action")?;
+            Ok(ReferentialAction::NoAction)
+        } else {
+            self.expect_word("set")?;
+            if self.consume(TokenKind::Null).is_some() {
+                Ok(ReferentialAction::SetNull)
+            } else {
+                self.expect(TokenKind::Default)?;
+                Ok(ReferentialAction::SetDefault)
+            }
+        }
+    }
+
+    // the parenthesized condition after CHECK
+    fn parse_check_condition(&mut self) -> Result<Expression, ParseError> {
+        self.expect(TokenKind::LParens)?;
+        let condition = self.parse_expression()?;
+        self.expect(TokenKind::RParens)?;
+        Ok(condition)
+    }
+
+    // ON CONFLICT [(columns) | ON CONSTRAINT name] DO {NOTHING | UPDATE SET ... [WHERE ...]}
+    // or ON DUPLICATE KEY UPDATE ...
+    fn parse_upsert(&mut self) -> Result<Option<Upsert>, ParseError> {
+        let Some(on) = self.consume(TokenKind::On) else {
+            return Ok(None);
+        };
+
+        if self.consume_word("duplicate").is_some() {
+            self.expect_word("key")?;
+            self.expect(TokenKind::Update)?;
+            let assignments = self.parse_assignments()?;
+
+            return Ok(Some(Upsert::OnDuplicateKeyUpdate {
+                span: Span::from(on.span.start..self.previous_end),
+                assignments,
+            }));
+        }
+
+        self.expect_word("conflict")?;
+        let target = if self.check(TokenKind::LParens) {
+            Some(ConflictTarget::Columns(self.parse_identifier_list()?))
+        } else if self.consume(TokenKind::On).is_some() {
+            self.expect_word("constraint")?;
+            Some(ConflictTarget::Constraint(self.parse_identifier()?))
+        } else {
+            None
+        };
+
+        self.expect_word("do")?;
+        let action = if self.consume_word("nothing").is_some() {
+            ConflictAction::DoNothing
+        } else {
+            self.expect(TokenKind::Update)?;
+            self.expect_word("set")?;
+            let assignments = self.parse_assignments()?;
+            let where_clause = if self.consume(TokenKind::Where).is_some() {
+                Some(self.parse_expression()?)
+            } else {
+                None
+            };
+            ConflictAction::DoUpdate {
+                assignments,
+                where_clause,
+            }
+        };
+
+        Ok(Some(Upsert::OnConflict {
+            span: Span::from(on.span.start..self.previous_end),
+            target,
+            action,
+        }))
+    }
+
+    // column = value [, ...], where t-sql also allows += and the like
+    fn parse_assignments(&mut self) -> Result<Vec<Assignment>, ParseError> {
+        let mut assignments = Vec::new();
+
+        loop {
+            let start = match self.current {
+                Some(ref token) => token.span.start,
+                None => return Err(ParseError::UnexpectedEOF),
+            };
+            let column = self.parse_object_name()?;
+            let operator = match self
+                .current
+                .as_ref()
+                .and_then(|token| assignment_operator(&token.kind))
+            {
+                Some(operator) => operator,
+                None => return Err(self.unexpected(TokenKind::Equals)),
+            };
+            self.advance();
+            let value = self.parse_expression()?;
+
+            assignments.push(Assignment {
+                span: Span::from(start..value.span().end),
+                column,
+                operator,
+                value,
+            });
+
+            if self.consume(TokenKind::Comma).is_none() {
+                break;
+            }
+        }
+
+        Ok(assignments)
+    }
+
+    // t-sql OUTPUT items. not a reserved word, so only taken as one here
+    fn parse_output(&mut self) -> Result<Option<Returning>, ParseError> {
+        let Some(output) = self.consume_word("output") else {
+            return Ok(None);
+        };
+        let items = self.parse_select_items()?;
+
+        Ok(Some(Returning {
+            span: Span::from(output.span.start..self.previous_end),
+            kind: ReturningKind::Output,
+            items,
+        }))
+    }
+
+    fn parse_returning(&mut self) -> Result<Option<Returning>, ParseError> {
+        let Some(returning) = self.consume_word("returning") else {
+            return Ok(None);
+        };
+        let items = self.parse_select_items()?;
+
+        Ok(Some(Returning {
+            span: Span::from(returning.span.start..self.previous_end),
+            kind: ReturningKind::Returning,
+            items,
+        }))
+    }
+
+    // comma separated select items
+    fn parse_select_items(&mut self) -> Result<Vec<SelectItem>, ParseError> {
+        let mut items = vec![self.parse_select_item()?];
+        while self.consume(TokenKind::Comma).is_some() {
+            items.push(self.parse_select_item()?);
+        }
+
+        Ok(items)
+    }
+
+    // a single SELECT block, up to and including HAVING
+    pub fn parse_select(&mut self) -> Result<SelectStatement, ParseError> {
+        let start_span = self.expect(TokenKind::Select)?.span;
+
+        let quantifier = if self.consume(TokenKind::All).is_some() {
+            Some(SelectQuantifier::All)
+        } else if self.consume(TokenKind::Distinct).is_some() {
+            if self.consume(TokenKind::On).is_some() {
+                self.expect(TokenKind::LParens)?;
+                let expressions = self.parse_expression_list()?;
+                self.expect(TokenKind::RParens)?;
+                Some(SelectQuantifier::DistinctOn(expressions))
+            } else {
+                Some(SelectQuantifier::Distinct)
+            }
+        } else {
+            None
+        };
 
-        // parse FROM clause
-        self.expect(TokenKind::From)?;
-        let from = self.parse_table_reference()?;
+        let columns = self.parse_select_items()?;
+
+        // parse optional FROM clause, e.g. `SELECT 1` has none
+        let from = if self.consume(TokenKind::From).is_some() {
+            Some(self.parse_from_list()?)
+        } else {
+            None
+        };
 
         // parse optional WHERE clause
-        let where_clause = if matches!(
-            self.current,
-            Some(Token {
-                kind: TokenKind::Where,
-                ..
-            })
-        ) {
-            self.advance();
+        let where_clause = if self.consume(TokenKind::Where).is_some() {
             Some(self.parse_where_clause()?)
         } else {
             None
         };
 
         // parse optional GROUP BY clause
-        let group_by = if matches!(
-            self.current,
-            Some(Token {
-                kind: TokenKind::Group,
-                ..
-            })
-        ) {
-            self.advance();
+        let group_by = if self.consume(TokenKind::Group).is_some() {
             self.expect(TokenKind::By)?;
             Some(self.parse_expression_list()?)
         } else {
             None
         };
 
-        let end_span = match self.current {
-            Some(ref token) => token.span.clone(),
-            None => start_span.clone(),
+        // parse HAVING clause
+        let having = if self.consume(TokenKind::Having).is_some() {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+
+        // parse WINDOW clause
+        let window = if self.consume_word("window").is_some() {
+            let mut windows = vec![self.parse_named_window()?];
+            while self.consume(TokenKind::Comma).is_some() {
+                windows.push(self.parse_named_window()?);
+            }
+            Some(windows)
+        } else {
+            None
         };
 
         Ok(SelectStatement {
-            span: Span::from(start_span.start..end_span.end),
+            span: Span::from(start_span.start..self.previous_end),
+            quantifier,
             columns,
             from,
             where_clause,
             group_by,
-            having: None, // TODO: implement having clause parsing
-                          // order_by: None, // TODO: implement ORDER BY clause parsing
+            having,
+            window,
+        })
+    }
+
+    // WITH [RECURSIVE] cte [, ...]
+    fn parse_with(&mut self) -> Result<With, ParseError> {
+        let start = self.expect(TokenKind::With)?.span.start;
+        // a CTE may itself be named `recursive`, then AS or its columns follow
+        let recursive = matches!(self.peek, Some(ref token) if token.kind == TokenKind::Identifier)
+            && self.consume_word("recursive").is_some();
+
+        let mut ctes = vec![self.parse_cte()?];
+        while self.consume(TokenKind::Comma).is_some() {
+            ctes.push(self.parse_cte()?);
+        }
+
+        Ok(With {
+            span: Span::from(start..self.previous_end),
+            recursive,
+            ctes,
+        })
+    }
+
+    // name [(column, ...)] AS [[NOT] MATERIALIZED] (SELECT ...)
+    fn parse_cte(&mut self) -> Result<Cte, ParseError> {
+        let name_token = self.expect(TokenKind::Identifier)?;
+        let name = self.get_identifier(&name_token);
+
+        let columns = if self.check(TokenKind::LParens) {
+            self.parse_identifier_list()?
+        } else {
+            Vec::new()
+        };
+
+        self.expect(TokenKind::As)?;
+
+        let materialized = if self.consume_word("materialized").is_some() {
+            Some(Materialization::Materialized)
+        } else if self.check(TokenKind::Not)
+            && matches!(self.peek, Some(ref token) if self.is_word(token, "materialized"))
+        {
+            self.advance(); // consume NOT
+            self.advance(); // consume MATERIALIZED
+            Some(Materialization::NotMaterialized)
+        } else {
+            None
+        };
+
+        self.expect(TokenKind::LParens)?;
+        let query = self.parse_subquery_body()?;
+
+        Ok(Cte {
+            span: Span::from(name_token.span.start..self.previous_end),
+            name,
+            columns,
+            materialized,
+            query: Box::new(query),
         })
     }
 
@@ -129,58 +1322,33 @@ impl<'a> Parser<'a> {
                 ..
             }) => {
                 let identifier = self.advance().unwrap();
+                let mut parts = vec![identifier];
 
-                // check if it's a qualified wildcard (e.g. table.*)
-                if matches!(
-                    self.current,
-                    Some(Token {
-                        kind: TokenKind::Dot,
-                        ..
-                    })
-                ) {
-                    self.advance(); // consume dot
-
-                    match &self.current {
-                        Some(Token {
-                            kind: TokenKind::Asterisk,
-                            span,
-                        }) => {
-                            let end_span = span.clone();
-                            self.advance(); // consume asterisk
-                            Ok(SelectItem::QualifiedWildcard {
-                                span: Span::from(identifier.span.start..end_span.end),
-                                qualifier: self.get_identifier_text(&identifier),
-                            })
-                        }
-                        _ => {
-                            // regular qualified column
-                            self.parse_regular_select_item(Some(identifier))
-                        }
+                // a dotted name, or a qualified wildcard (e.g. table.*)
+                while self.consume(TokenKind::Dot).is_some() {
+                    if let Some(asterisk) = self.consume(TokenKind::Asterisk) {
+                        let qualifier = parts.iter().map(|t| self.get_identifier(t)).collect();
+                        return Ok(SelectItem::QualifiedWildcard {
+                            span: Span::from(parts[0].span.start..asterisk.span.end),
+                            qualifier: ObjectName(qualifier),
+                        });
                     }
-                } else {
-                    // regular unqualified column
-                    self.parse_regular_select_item(Some(identifier))
+                    parts.push(self.expect(TokenKind::Identifier)?);
                 }
+
+                let expr = self.parse_name_parts(parts)?;
+                self.parse_regular_select_item(Some(expr))
             }
-            Some(token) => Err(ParseError::UnexpectedToken {
-                expected: TokenKind::Identifier,
-                found: token.kind.clone(),
-                span: token.span.clone(),
-            }),
-            None => Err(ParseError::UnexpectedEOF),
+            _ => self.parse_regular_select_item(None),
         }
     }
 
     fn parse_regular_select_item(
         &mut self,
-        initial_identifier: Option<Token>,
+        initial_expression: Option<Expression>,
     ) -> Result<SelectItem, ParseError> {
-        let expr = if let Some(ident) = initial_identifier {
-            Expression::Column {
-                span: ident.span.clone().into(),
-                name: self.get_identifier_text(&ident),
-                table: None,
-            }
+        let expr = if let Some(initial) = initial_expression {
+            self.parse_binary_operations(initial, 0)?
         } else {
             self.parse_expression()?
         };
@@ -195,127 +1363,626 @@ impl<'a> Parser<'a> {
         ) {
             self.advance(); // consume AS
             let token = self.expect(TokenKind::Identifier)?;
-            Some(self.get_identifier_text(&token))
-        } else if matches!(
-            self.current,
-            Some(Token {
-                kind: TokenKind::Identifier,
-                ..
-            })
-        ) {
-            let token = self.advance().unwrap();
-            Some(self.get_identifier_text(&token))
+            Some(self.get_identifier(&token))
+        } else {
+            self.consume_alias()
+                .map(|token| self.get_identifier(&token))
+        };
+
+        Ok(SelectItem::Expression {
+            span: expr.span(),
+            expr,
+            alias,
+        })
+    }
+
+    fn parse_expression(&mut self) -> Result<Expression, ParseError> {
+        self.parse_expression_with_precedence(0)
+    }
+
+    // precedence climbing: parse an operand, then fold in every binary operator
+    // that binds tighter than `min_precedence`
+    fn parse_expression_with_precedence(
+        &mut self,
+        min_precedence: u8,
+    ) -> Result<Expression, ParseError> {
+        let left = self.parse_prefix_expression()?;
+        self.parse_binary_operations(left, min_precedence)
+    }
+
+    fn parse_binary_operations(
+        &mut self,
+        mut left: Expression,
+        min_precedence: u8,
+    ) -> Result<Expression, ParseError> {
+        loop {
+            // IN, BETWEEN, LIKE and IS predicates bind like a comparison
+            if self.at_predicate() {
+                if Operator::Equals.precedence() <= min_precedence {
+                    break;
+                }
+                left = self.parse_predicate(left)?;
+                continue;
+            }
+
+            // postgres casts are postfix and bind tighter than anything else
+            if self.check(TokenKind::DoubleColon) {
+                if CAST_PRECEDENCE <= min_precedence {
+                    break;
+                }
+                self.advance(); // consume ::
+                let data_type = self.parse_data_type()?;
+
+                left = Expression::Cast {
+                    span: Span::from(left.span().start..data_type.span.end),
+                    kind: CastKind::DoubleColon,
+                    expr: Box::new(left),
+                    data_type,
+                    style: None,
+                };
+                continue;
+            }
+
+            // so are array subscripts
+            if self.check(TokenKind::LBracket) {
+                if CAST_PRECEDENCE <= min_precedence {
+                    break;
+                }
+                self.advance(); // consume [
+                let index = self.parse_expression()?;
+                let end = self.expect(TokenKind::RBracket)?.span.end;
+
+                left = Expression::Subscript {
+                    span: Span::from(left.span().start..end),
+                    expr: Box::new(left),
+                    index: Box::new(index),
+                };
+                continue;
+            }
+
+            let Some(operator) = self.current.as_ref().and_then(|t| binary_operator(&t.kind))
+            else {
+                break;
+            };
+
+            // stopping at equal precedence makes every operator left associative
+            let precedence = operator.precedence();
+            if precedence <= min_precedence {
+                break;
+            }
+            self.advance(); // consume the operator
+
+            let right = self.parse_expression_with_precedence(precedence)?;
+            let span = Span::from(left.span().start..right.span().end);
+
+            left = Expression::BinaryOperation {
+                span,
+                left: Box::new(left),
+                op: operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(left)
+    }
+
+    // whether the current token starts a predicate, possibly after NOT
+    fn at_predicate(&self) -> bool {
+        let is_predicate = |token: &Token| {
+            matches!(
+                token.kind,
+                TokenKind::In | TokenKind::Between | TokenKind::Like | TokenKind::ILike
+            ) || self.is_word(token, "similar")
+        };
+
+        match self.current {
+            // SIMILAR isn't reserved, it's an alias unless TO follows
+            Some(ref token) if self.is_word(token, "similar") => {
+                matches!(self.peek, Some(ref next) if self.is_word(next, "to"))
+            }
+            Some(ref token) if token.kind == TokenKind::Is || is_predicate(token) => true,
+            Some(ref token) if token.kind == TokenKind::Not => {
+                matches!(self.peek, Some(ref next) if is_predicate(next))
+            }
+            _ => false,
+        }
+    }
+
+    // the rest of a predicate about `expr`, from the keyword (or NOT) on
+    fn parse_predicate(&mut self, expr: Expression) -> Result<Expression, ParseError> {
+        let start = expr.span().start;
+        // the operands of a predicate stop at the next comparison, and BETWEEN's
+        // AND is never read as a logical one
+        let precedence = Operator::Equals.precedence();
+
+        if self.consume(TokenKind::Is).is_some() {
+            let negated = self.consume(TokenKind::Not).is_some();
+
+            if self.consume(TokenKind::Distinct).is_some() {
+                self.expect(TokenKind::From)?;
+                let right = self.parse_expression_with_precedence(precedence)?;
+                return Ok(Expression::IsDistinctFrom {
+                    span: Span::from(start..right.span().end),
+                    left: Box::new(expr),
+                    right: Box::new(right),
+                    negated,
+                });
+            }
+
+            let value = if self.consume(TokenKind::Null).is_some() {
+                IsValue::Null
+            } else if self.consume(TokenKind::True).is_some() {
+                IsValue::True
+            } else if self.consume(TokenKind::False).is_some() {
+                IsValue::False
+            } else {
+                return Err(self.unexpected(TokenKind::Null));
+            };
+
+            return Ok(Expression::Is {
+                span: Span::from(start..self.previous_end),
+                expr: Box::new(expr),
+                value,
+                negated,
+            });
+        }
+
+        let negated = self.consume(TokenKind::Not).is_some();
+
+        if self.consume(TokenKind::In).is_some() {
+            self.expect(TokenKind::LParens)?;
+
+            if self.check(TokenKind::Select) || self.check(TokenKind::With) {
+                let query = self.parse_subquery_body()?;
+                return Ok(Expression::InSubquery {
+                    span: Span::from(start..self.previous_end),
+                    expr: Box::new(expr),
+                    query: Box::new(query),
+                    negated,
+                });
+            }
+
+            let list = self.parse_expression_list()?;
+            let end = self.expect(TokenKind::RParens)?.span.end;
+            return Ok(Expression::InList {
+                span: Span::from(start..end),
+                expr: Box::new(expr),
+                list,
+                negated,
+            });
+        }
+
+        if self.consume(TokenKind::Between).is_some() {
+            let low = self.parse_expression_with_precedence(precedence)?;
+            self.expect(TokenKind::And)?;
+            let high = self.parse_expression_with_precedence(precedence)?;
+            return Ok(Expression::Between {
+                span: Span::from(start..high.span().end),
+                expr: Box::new(expr),
+                low: Box::new(low),
+                high: Box::new(high),
+                negated,
+            });
+        }
+
+        let kind = if self.consume(TokenKind::Like).is_some() {
+            LikeKind::Like
+        } else if self.consume(TokenKind::ILike).is_some() {
+            LikeKind::ILike
+        } else {
+            self.expect_word("similar")?;
+            self.expect_word("to")?;
+            LikeKind::SimilarTo
+        };
+
+        let pattern = self.parse_expression_with_precedence(precedence)?;
+        let escape = if self.consume_word("escape").is_some() {
+            Some(Box::new(self.parse_expression_with_precedence(precedence)?))
+        } else {
+            None
+        };
+
+        Ok(Expression::Like {
+            span: Span::from(start..self.previous_end),
+            expr: Box::new(expr),
+            kind,
+            pattern: Box::new(pattern),
+            escape,
+            negated,
+        })
+    }
+
+    // an operand, along with any unary operators or parentheses around it
+    fn parse_prefix_expression(&mut self) -> Result<Expression, ParseError> {
+        let start = match self.current {
+            Some(ref token) => token.span.start,
+            None => return Err(ParseError::UnexpectedEOF),
+        };
+
+        let op = match self.current.as_ref().map(|t| &t.kind) {
+            Some(TokenKind::Not) => UnaryOperator::Not,
+            Some(TokenKind::Plus) => UnaryOperator::Plus,
+            Some(TokenKind::Minus) => UnaryOperator::Minus,
+            Some(TokenKind::Tilde) => UnaryOperator::BitwiseNot,
+            Some(TokenKind::LParens)
+                if matches!(
+                    self.peek,
+                    Some(Token {
+                        kind: TokenKind::Select | TokenKind::With,
+                        ..
+                    })
+                ) =>
+            {
+                self.advance(); // consume (
+                let query = self.parse_subquery_body()?;
+
+                return Ok(Expression::Subquery {
+                    span: Span::from(start..self.previous_end),
+                    query: Box::new(query),
+                });
+            }
+            Some(TokenKind::Exists) => {
+                self.advance(); // consume EXISTS
+                self.expect(TokenKind::LParens)?;
+                let query = self.parse_subquery_body()?;
+
+                return Ok(Expression::Exists {
+                    span: Span::from(start..self.previous_end),
+                    query: Box::new(query),
+                });
+            }
+            Some(TokenKind::Case) => return self.parse_case(),
+            Some(TokenKind::LParens) => {
+                self.advance(); // consume (
+                let expr = self.parse_expression()?;
+                let end = self.expect(TokenKind::RParens)?.span.end;
+
+                return Ok(Expression::Nested {
+                    span: Span::from(start..end),
+                    expr: Box::new(expr),
+                });
+            }
+            _ => return self.parse_primary_expression(),
+        };
+        self.advance(); // consume the operator
+
+        let expr = self.parse_expression_with_precedence(op.precedence())?;
+
+        Ok(Expression::UnaryOperation {
+            span: Span::from(start..expr.span().end),
+            op,
+            expr: Box::new(expr),
+        })
+    }
+
+    fn parse_table_reference(&mut self) -> Result<TableReference, ParseError> {
+        let start_token = self.current.clone().ok_or(ParseError::UnexpectedEOF)?;
+        let name = self.parse_object_name()?;
+        let alias = self.parse_table_alias()?;
+
+        Ok(TableReference {
+            span: Span::from(start_token.span.start..self.previous_end),
+            name,
+            alias: alias.map(|t| self.get_identifier(&t)),
+        })
+    }
+
+    // a dotted name of one or more parts, e.g. `users` or `server.db.dbo.users`
+    fn parse_object_name(&mut self) -> Result<ObjectName, ParseError> {
+        let mut parts = vec![self.parse_identifier()?];
+        while self.consume(TokenKind::Dot).is_some() {
+            parts.push(self.parse_identifier()?);
+        }
+
+        Ok(ObjectName(parts))
+    }
+
+    // optional alias after a table, with or without AS
+    fn parse_table_alias(&mut self) -> Result<Option<Token>, ParseError> {
+        if self.consume(TokenKind::As).is_some() {
+            Ok(Some(self.expect(TokenKind::Identifier)?))
+        } else {
+            Ok(self.consume_alias())
+        }
+    }
+
+    // an alias without AS. a word that may follow a table or a select item
+    // (e.g. t-sql OUTPUT after a DELETE target) ends it instead
+    fn consume_alias(&mut self) -> Option<Token> {
+        match self.current {
+            Some(ref token)
+                if ALIAS_STOP_WORDS
+                    .iter()
+                    .any(|word| self.is_word(token, word)) =>
+            {
+                None
+            }
+            _ => self.consume(TokenKind::Identifier),
+        }
+    }
+
+    // a single table, a derived table `(SELECT ...) [AS] alias`, or a join tree
+    // in parentheses
+    fn parse_table_factor(&mut self) -> Result<TableExpression, ParseError> {
+        let Some(open) = self.consume(TokenKind::LParens) else {
+            return Ok(TableExpression::Table(self.parse_table_reference()?));
+        };
+
+        // anything but a query in the parens is a join tree
+        let query_follows = |token: &Option<Token>| {
+            matches!(
+                token,
+                Some(Token {
+                    kind: TokenKind::Select | TokenKind::With,
+                    ..
+                })
+            )
+        };
+        let is_query = query_follows(&self.current)
+            || (self.check(TokenKind::LParens) && query_follows(&self.peek));
+        if !is_query {
+            let expr = self.parse_table_expression()?;
+            let close = self.expect(TokenKind::RParens)?;
+
+            return Ok(TableExpression::Nested {
+                span: Span::from(open.span.start..close.span.end),
+                expr: Box::new(expr),
+            });
+        }
+
+        let query = self.parse_subquery_body()?;
+        let mut end = self.previous_end;
+        let alias = self.parse_table_alias()?.map(|token| {
+            end = token.span.end;
+            self.get_identifier(&token)
+        });
+
+        Ok(TableExpression::Derived {
+            span: Span::from(open.span.start..end),
+            query: Box::new(query),
+            alias,
+        })
+    }
+
+    // the rest of a `(SELECT ...)` once the opening paren is consumed
+    fn parse_subquery_body(&mut self) -> Result<Query, ParseError> {
+        let start = match self.current {
+            Some(ref token) => token.span.start,
+            None => return Err(ParseError::UnexpectedEOF),
+        };
+
+        let mut query = self.parse_query()?;
+        self.expect(TokenKind::RParens)?;
+        self.hand_back_comments(&mut query, start);
+
+        Ok(query)
+    }
+
+    // comments ahead of a nested query's `start` belong to the statement
+    // around it, so they're put back for that statement to take
+    fn hand_back_comments(&mut self, query: &mut Query, start: usize) {
+        let (before, inside) = std::mem::take(&mut query.comments)
+            .into_iter()
+            .partition(|comment| comment.span.start < start);
+        query.comments = inside;
+        self.comments.splice(0..0, before);
+    }
+
+    fn parse_from_list(&mut self) -> Result<Vec<TableExpression>, ParseError> {
+        let mut items = Vec::new();
+
+        loop {
+            items.push(self.parse_table_expression()?);
+
+            if self.consume(TokenKind::Comma).is_none() {
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    // a table followed by any number of joins, folded left to right
+    fn parse_table_expression(&mut self) -> Result<TableExpression, ParseError> {
+        let mut left = self.parse_table_factor()?;
+
+        while let Some((natural, kind)) = self.parse_join_kind()? {
+            let right = self.parse_table_factor()?;
+            let mut end = right.span().end;
+
+            let constraint = if self.consume(TokenKind::On).is_some() {
+                let condition = self.parse_expression()?;
+                end = condition.span().end;
+                Some(JoinConstraint::On(condition))
+            } else if self.consume_word("using").is_some() {
+                let columns = self.parse_identifier_list()?;
+                end = self.previous_end;
+                Some(JoinConstraint::Using(columns))
+            } else {
+                None
+            };
+
+            left = TableExpression::Join(Box::new(Join {
+                span: Span::from(left.span().start..end),
+                left,
+                right,
+                kind,
+                natural,
+                constraint,
+            }));
+        }
+
+        Ok(left)
+    }
+
+    // the keywords introducing a join, if the current token starts one
+    fn parse_join_kind(&mut self) -> Result<Option<(bool, JoinKind)>, ParseError> {
+        let natural = self.consume(TokenKind::Natural).is_some();
+
+        let kind = match self.current.as_ref().map(|t| &t.kind) {
+            Some(TokenKind::Join) => JoinKind::Join,
+            Some(TokenKind::Inner) => {
+                self.advance();
+                JoinKind::Inner
+            }
+            Some(TokenKind::Left) => {
+                self.advance();
+                JoinKind::Left {
+                    outer: self.consume(TokenKind::Outer).is_some(),
+                }
+            }
+            Some(TokenKind::Right) => {
+                self.advance();
+                JoinKind::Right {
+                    outer: self.consume(TokenKind::Outer).is_some(),
+                }
+            }
+            Some(TokenKind::Full) => {
+                self.advance();
+                JoinKind::Full {
+                    outer: self.consume(TokenKind::Outer).is_some(),
+                }
+            }
+            Some(TokenKind::Cross) => {
+                self.advance();
+                JoinKind::Cross
+            }
+            _ if natural => return Err(self.unexpected(TokenKind::Join)),
+            _ => return Ok(None),
+        };
+        self.expect(TokenKind::Join)?;
+
+        Ok(Some((natural, kind)))
+    }
+
+    fn parse_order_by_list(&mut self) -> Result<Vec<OrderByItem>, ParseError> {
+        let mut items = vec![self.parse_order_by_item()?];
+        while self.consume(TokenKind::Comma).is_some() {
+            items.push(self.parse_order_by_item()?);
+        }
+
+        Ok(items)
+    }
+
+    // expr [ASC | DESC] [NULLS {FIRST | LAST}]
+    fn parse_order_by_item(&mut self) -> Result<OrderByItem, ParseError> {
+        let expr = self.parse_expression()?;
+        let mut end = expr.span().end;
+
+        let direction = if let Some(token) = self.consume_word("asc") {
+            end = token.span.end;
+            Some(SortDirection::Asc)
+        } else if let Some(token) = self.consume_word("desc") {
+            end = token.span.end;
+            Some(SortDirection::Desc)
         } else {
             None
         };
 
-        Ok(SelectItem::Expression {
-            span: expr.span(),
+        let nulls = if self.consume_word("nulls").is_some() {
+            if let Some(token) = self.consume_word("first") {
+                end = token.span.end;
+                Some(NullsOrder::First)
+            } else {
+                end = self.expect_word("last")?.span.end;
+                Some(NullsOrder::Last)
+            }
+        } else {
+            None
+        };
+
+        Ok(OrderByItem {
+            span: Span::from(expr.span().start..end),
             expr,
-            alias,
+            direction,
+            nulls,
         })
     }
 
-    fn parse_expression(&mut self) -> Result<Expression, ParseError> {
-        // Parse the left side of any potential binary operation
-        let mut left = self.parse_primary_expression()?;
-
-        // Look for binary operators
-        while let Some(token) = &self.current {
-            let op = match token.kind {
-                TokenKind::Equals => Some(Operator::Equals),
-                TokenKind::NotEquals => Some(Operator::NotEquals),
-                TokenKind::LessThan => Some(Operator::LessThan),
-                TokenKind::GreaterThan => Some(Operator::GreaterThan),
-                TokenKind::LessEquals => Some(Operator::LessEquals),
-                TokenKind::GreaterEquals => Some(Operator::GreaterEquals),
-                _ => None,
-            };
+    fn parse_limit(&mut self) -> Result<Option<Expression>, ParseError> {
+        if self.consume_word("limit").is_none() {
+            return Ok(None);
+        }
 
-            if let Some(operator) = op {
-                let start_span = left.span();
-                self.advance(); // consume the operator
+        Ok(Some(self.parse_expression()?))
+    }
 
-                let right = self.parse_primary_expression()?;
-                let end_span = right.span();
+    fn parse_offset(&mut self) -> Result<Option<Offset>, ParseError> {
+        let Some(keyword) = self.consume_word("offset") else {
+            return Ok(None);
+        };
 
-                left = Expression::BinaryOperation {
-                    span: Span::from(start_span.start..end_span.end),
-                    left: Box::new(left),
-                    op: operator,
-                    right: Box::new(right),
-                };
-            } else {
-                break;
-            }
-        }
+        let value = self.parse_expression()?;
+        let mut end = value.span().end;
+        let rows = self.parse_rows_keyword().map(|(rows, token)| {
+            end = token.span.end;
+            rows
+        });
 
-        Ok(left)
+        Ok(Some(Offset {
+            span: Span::from(keyword.span.start..end),
+            value,
+            rows,
+        }))
     }
 
-    fn parse_table_reference(&mut self) -> Result<TableReference, ParseError> {
-        let start_token = self.current.clone().ok_or(ParseError::UnexpectedEOF)?;
-
-        // parse optional schema name
-        let (schema, table_name) = if let Some(Token {
-            kind: TokenKind::Identifier,
-            ..
-        }) = self.current
-        {
-            let schema_token = self.advance().unwrap();
+    fn parse_fetch(&mut self) -> Result<Option<Fetch>, ParseError> {
+        let Some(keyword) = self.consume_word("fetch") else {
+            return Ok(None);
+        };
 
-            // check for schema separator
-            if matches!(
-                self.current,
-                Some(Token {
-                    kind: TokenKind::Dot,
-                    ..
-                })
-            ) {
-                self.advance(); // consume dot
+        let next = if self.consume_word("next").is_some() {
+            true
+        } else {
+            self.expect_word("first")?;
+            false
+        };
 
-                // parse table name
-                let table_token = self.expect(TokenKind::Identifier)?;
-                (Some(self.get_identifier_text(&schema_token)), table_token)
-            } else {
-                (None, schema_token)
+        // the row count is optional and defaults to one
+        let (quantity, rows) = match self.parse_rows_keyword() {
+            Some((rows, _)) => (None, rows),
+            None => {
+                let quantity = self.parse_expression()?;
+                match self.parse_rows_keyword() {
+                    Some((rows, _)) => (Some(quantity), rows),
+                    None => return Err(self.unexpected(TokenKind::Identifier)),
+                }
             }
-        } else {
-            return Err(ParseError::UnexpectedToken {
-                expected: TokenKind::Identifier,
-                found: self
-                    .current
-                    .as_ref()
-                    .map_or(TokenKind::Identifier, |t| t.kind.clone()),
-                span: self.current.as_ref().map_or(0..0, |t| t.span.clone()),
-            });
         };
+        let end = self.expect_word("only")?.span.end;
 
-        // check for optional alias
-        let alias = if matches!(
-            self.current,
-            Some(Token {
-                kind: TokenKind::Identifier,
-                ..
-            })
-        ) {
-            Some(self.advance().unwrap())
+        Ok(Some(Fetch {
+            span: Span::from(keyword.span.start..end),
+            next,
+            quantity,
+            rows,
+        }))
+    }
+
+    fn parse_rows_keyword(&mut self) -> Option<(RowsKeyword, Token)> {
+        if let Some(token) = self.consume_word("row") {
+            Some((RowsKeyword::Row, token))
         } else {
-            None
-        };
+            self.consume_word("rows")
+                .map(|token| (RowsKeyword::Rows, token))
+        }
+    }
 
-        Ok(TableReference {
-            span: Span::from(
-                start_token.span.start
-                    ..alias
-                        .as_ref()
-                        .map(|t| t.span.end)
-                        .unwrap_or(table_name.span.end),
-            ),
-            name: self.get_identifier_text(&table_name),
-            alias: alias.map(|t| self.get_identifier_text(&t)),
-            schema,
-        })
+    fn parse_identifier(&mut self) -> Result<Identifier, ParseError> {
+        let token = self.expect(TokenKind::Identifier)?;
+        Ok(self.get_identifier(&token))
+    }
+
+    // a parenthesized, comma separated list of identifiers, e.g. `(a, b)`
+    fn parse_identifier_list(&mut self) -> Result<Vec<Identifier>, ParseError> {
+        self.expect(TokenKind::LParens)?;
+        let mut identifiers = vec![self.parse_identifier()?];
+        while self.consume(TokenKind::Comma).is_some() {
+            identifiers.push(self.parse_identifier()?);
+        }
+        self.expect(TokenKind::RParens)?;
+
+        Ok(identifiers)
     }
 
     fn parse_where_clause(&mut self) -> Result<WhereClause, ParseError> {
@@ -355,20 +2022,39 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_primary_expression(&mut self) -> Result<Expression, ParseError> {
-        match self.current.take() {
+        match self.current.clone() {
             Some(Token {
                 kind: TokenKind::Identifier,
-                span,
+                ..
             }) => {
+                let mut parts = vec![self.advance().unwrap()];
+                while self.consume(TokenKind::Dot).is_some() {
+                    parts.push(self.expect(TokenKind::Identifier)?);
+                }
+                self.parse_name_parts(parts)
+            }
+
+            Some(Token {
+                kind: TokenKind::Cast | TokenKind::TryCast,
+                ..
+            }) => self.parse_cast(),
+
+            Some(Token {
+                kind: TokenKind::Convert | TokenKind::TryConvert,
+                ..
+            }) => self.parse_convert(),
+
+            // LEFT and RIGHT are keywords, but also string functions
+            Some(
+                ref token @ Token {
+                    kind: TokenKind::Left | TokenKind::Right,
+                    ..
+                },
+            ) if matches!(self.peek, Some(ref next) if next.kind == TokenKind::LParens) => {
+                let start = token.span.start;
+                let name = ObjectName(vec![self.get_identifier(token)]);
                 self.advance();
-                Ok(Expression::Column {
-                    span: span.clone().into(),
-                    name: self.get_identifier_text(&Token {
-                        kind: TokenKind::Identifier,
-                        span: span.clone(),
-                    }),
-                    table: None,
-                })
+                self.parse_function_call(name, start)
             }
 
             Some(Token {
@@ -378,49 +2064,509 @@ impl<'a> Parser<'a> {
                 self.advance();
                 Ok(Expression::Literal {
                     span: span.clone().into(),
-                    value: LiteralValue::String(self.get_string_literal(&span)),
+                    value: self.get_string_literal(&span),
                 })
             }
 
             Some(Token {
-                kind: TokenKind::Number,
+                kind:
+                    kind @ (TokenKind::Number
+                    | TokenKind::HexNumber
+                    | TokenKind::BinaryNumber
+                    | TokenKind::Null
+                    | TokenKind::True
+                    | TokenKind::False),
                 span,
             }) => {
                 self.advance();
+                let value = match kind {
+                    TokenKind::Number => LiteralValue::Number(self.get_number_literal(&span)),
+                    TokenKind::HexNumber => LiteralValue::Hex(self.get_number_literal(&span)),
+                    TokenKind::BinaryNumber => LiteralValue::Binary(self.get_number_literal(&span)),
+                    TokenKind::True => LiteralValue::Boolean(true),
+                    TokenKind::False => LiteralValue::Boolean(false),
+                    _ => LiteralValue::Null,
+                };
+
                 Ok(Expression::Literal {
-                    span: span.clone().into(),
-                    value: LiteralValue::Number(self.get_number_literal(&span)),
+                    span: span.into(),
+                    value,
                 })
             }
 
-            Some(token) => Err(ParseError::UnexpectedToken {
-                expected: TokenKind::Identifier,
-                found: token.kind,
-                span: token.span,
-            }),
+            Some(Token {
+                kind: TokenKind::Default,
+                span,
+            }) => {
+                self.advance();
+                Ok(Expression::Default { span: span.into() })
+            }
 
-            None => Err(ParseError::UnexpectedEOF),
+            _ => Err(self.unexpected(TokenKind::Identifier)),
         }
     }
 
-    pub fn get_identifier_text(&self, token: &Token) -> String {
-        let text = &self.input[token.span.clone()];
-        // handle bracketed identifiers (e.g. [column1] -> column1)
-        if text.starts_with('[') && text.ends_with(']') {
-            text[1..text.len() - 1].to_string()
+    // a column or function call named by the dotted name `parts`
+    fn parse_name_parts(&mut self, parts: Vec<Token>) -> Result<Expression, ParseError> {
+        let start = parts[0].span.start;
+        let end = parts[parts.len() - 1].span.end;
+        let name = ObjectName(parts.iter().map(|t| self.get_identifier(t)).collect());
+
+        if self.check(TokenKind::LParens) {
+            return self.parse_function_call(name, start);
+        }
+
+        Ok(Expression::Column {
+            span: Span::from(start..end),
+            name,
+        })
+    }
+
+    // name(...) [WITHIN GROUP (ORDER BY ...)] [FILTER (WHERE ...)], from the
+    // opening paren on
+    fn parse_function_call(
+        &mut self,
+        name: ObjectName,
+        start: usize,
+    ) -> Result<Expression, ParseError> {
+        self.expect(TokenKind::LParens)?;
+
+        let quantifier = self.parse_quantifier();
+        let args = if let Some(asterisk) = self.consume(TokenKind::Asterisk) {
+            vec![Expression::Asterisk {
+                span: asterisk.span.into(),
+            }]
+        } else if self.check(TokenKind::RParens) {
+            Vec::new()
+        } else {
+            self.parse_expression_list()?
+        };
+
+        // aggregate ordering, e.g. string_agg(name, ',' ORDER BY name)
+        let order_by = if self.consume(TokenKind::Order).is_some() {
+            self.expect(TokenKind::By)?;
+            self.parse_order_by_list()?
+        } else {
+            Vec::new()
+        };
+        self.expect(TokenKind::RParens)?;
+
+        // neither word is reserved, so without what follows them they're an alias
+        let within_group = if matches!(self.peek, Some(ref token) if token.kind == TokenKind::Group)
+            && self.consume_word("within").is_some()
+        {
+            self.advance(); // consume GROUP
+            self.expect(TokenKind::LParens)?;
+            self.expect(TokenKind::Order)?;
+            self.expect(TokenKind::By)?;
+            let items = self.parse_order_by_list()?;
+            self.expect(TokenKind::RParens)?;
+            items
+        } else {
+            Vec::new()
+        };
+
+        let filter = if matches!(self.peek, Some(ref token) if token.kind == TokenKind::LParens)
+            && self.consume_word("filter").is_some()
+        {
+            self.expect(TokenKind::LParens)?;
+            self.expect(TokenKind::Where)?;
+            let condition = self.parse_expression()?;
+            self.expect(TokenKind::RParens)?;
+            Some(Box::new(condition))
+        } else {
+            None
+        };
+
+        // OVER needs a window after it, otherwise it's an alias
+        let over = if matches!(
+            self.peek,
+            Some(Token {
+                kind: TokenKind::LParens | TokenKind::Identifier,
+                ..
+            })
+        ) && self.consume_word("over").is_some()
+        {
+            if self.check(TokenKind::LParens) {
+                Some(Window::Specification(Box::new(self.parse_window_spec()?)))
+            } else {
+                Some(Window::Named(self.parse_identifier()?))
+            }
+        } else {
+            None
+        };
+
+        Ok(Expression::Function {
+            span: Span::from(start..self.previous_end),
+            name,
+            args,
+            quantifier,
+            order_by,
+            within_group,
+            filter,
+            over,
+        })
+    }
+
+    // CAST(expr AS type) or TRY_CAST(expr AS type)
+    fn parse_cast(&mut self) -> Result<Expression, ParseError> {
+        let (kind, start) = match self.advance() {
+            Some(token) if token.kind == TokenKind::TryCast => {
+                (CastKind::TryCast, token.span.start)
+            }
+            Some(token) => (CastKind::Cast, token.span.start),
+            None => return Err(ParseError::UnexpectedEOF),
+        };
+
+        self.expect(TokenKind::LParens)?;
+        let expr = self.parse_expression()?;
+        self.expect(TokenKind::As)?;
+        let data_type = self.parse_data_type()?;
+        let end = self.expect(TokenKind::RParens)?.span.end;
+
+        Ok(Expression::Cast {
+            span: Span::from(start..end),
+            kind,
+            expr: Box::new(expr),
+            data_type,
+            style: None,
+        })
+    }
+
+    // CONVERT(type, expr[, style]) or TRY_CONVERT(...)
+    fn parse_convert(&mut self) -> Result<Expression, ParseError> {
+        let (kind, start) = match self.advance() {
+            Some(token) if token.kind == TokenKind::TryConvert => {
+                (CastKind::TryConvert, token.span.start)
+            }
+            Some(token) => (CastKind::Convert, token.span.start),
+            None => return Err(ParseError::UnexpectedEOF),
+        };
+
+        self.expect(TokenKind::LParens)?;
+        let data_type = self.parse_data_type()?;
+        self.expect(TokenKind::Comma)?;
+        let expr = self.parse_expression()?;
+        let style = if self.consume(TokenKind::Comma).is_some() {
+            Some(Box::new(self.parse_expression()?))
+        } else {
+            None
+        };
+        let end = self.expect(TokenKind::RParens)?.span.end;
+
+        Ok(Expression::Cast {
+            span: Span::from(start..end),
+            kind,
+            expr: Box::new(expr),
+            data_type,
+            style,
+        })
+    }
+
+    // name[(arguments)] [WITH | WITHOUT TIME ZONE] [[n]]...
+    fn parse_data_type(&mut self) -> Result<DataType, ParseError> {
+        let first = self.expect(TokenKind::Identifier)?;
+        let mut end = first.span.end;
+        let mut name = self.get_identifier(&first);
+
+        // the few standard types spelled with two words
+        if name.quote_style == QuoteStyle::None {
+            let second = match name.value.to_ascii_lowercase().as_str() {
+                "double" => self.consume_word("precision"),
+                "character" | "char" => self.consume_word("varying"),
+                _ => None,
+            };
+            if let Some(second) = second {
+                name.value = format!("{} {}", name.value, &self.input[second.span.clone()]);
+                end = second.span.end;
+            }
+        }
+
+        let mut parts = vec![name];
+        while self.consume(TokenKind::Dot).is_some() {
+            let part = self.expect(TokenKind::Identifier)?;
+            end = part.span.end;
+            parts.push(self.get_identifier(&part));
+        }
+
+        let mut arguments = Vec::new();
+        if self.consume(TokenKind::LParens).is_some() {
+            loop {
+                if let Some(number) = self.consume(TokenKind::Number) {
+                    arguments.push(TypeArgument::Number(self.get_number_literal(&number.span)));
+                } else if self.consume_word("max").is_some() {
+                    arguments.push(TypeArgument::Max);
+                } else {
+                    return Err(self.unexpected(TokenKind::Number));
+                }
+                if self.consume(TokenKind::Comma).is_none() {
+                    break;
+                }
+            }
+            end = self.expect(TokenKind::RParens)?.span.end;
+        }
+
+        // WITH here can only be part of the type when TIME follows it
+        let time_zone = if self.check(TokenKind::With)
+            && matches!(self.peek, Some(ref token) if self.is_word(token, "time"))
+        {
+            self.advance();
+            Some(TimeZone::With)
+        } else if self.consume_word("without").is_some() {
+            Some(TimeZone::Without)
+        } else {
+            None
+        };
+        if time_zone.is_some() {
+            self.expect_word("time")?;
+            end = self.expect_word("zone")?.span.end;
+        }
+
+        let mut array_dimensions = Vec::new();
+        while self.consume(TokenKind::LBracket).is_some() {
+            let bound = self
+                .consume(TokenKind::Number)
+                .map(|number| self.get_number_literal(&number.span));
+            array_dimensions.push(bound);
+            end = self.expect(TokenKind::RBracket)?.span.end;
+        }
+
+        Ok(DataType {
+            span: Span::from(first.span.start..end),
+            name: ObjectName(parts),
+            arguments,
+            time_zone,
+            array_dimensions,
+        })
+    }
+
+    // whether `token` is the unquoted word `word`, in any case
+    fn is_word(&self, token: &Token, word: &str) -> bool {
+        token.kind == TokenKind::Identifier
+            && self.input[token.span.clone()].eq_ignore_ascii_case(word)
+    }
+
+    // consume the current token only if it's the unquoted word `word`
+    fn consume_word(&mut self, word: &str) -> Option<Token> {
+        match self.current {
+            Some(ref token) if self.is_word(token, word) => self.advance(),
+            _ => None,
+        }
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<Token, ParseError> {
+        self.consume_word(word)
+            .ok_or_else(|| self.unexpected(TokenKind::Identifier))
+    }
+
+    // CASE [operand] WHEN ... THEN ... [WHEN ...] [ELSE ...] END
+    fn parse_case(&mut self) -> Result<Expression, ParseError> {
+        let start = self.expect(TokenKind::Case)?.span.start;
+
+        let operand = if self.check(TokenKind::When) {
+            None
         } else {
-            text.to_string()
+            Some(Box::new(self.parse_expression()?))
+        };
+
+        let mut conditions = Vec::new();
+        while let Some(when) = self.consume(TokenKind::When) {
+            let condition = self.parse_expression()?;
+            self.expect(TokenKind::Then)?;
+            let result = self.parse_expression()?;
+            conditions.push(WhenClause {
+                span: Span::from(when.span.start..result.span().end),
+                condition,
+                result,
+            });
         }
+        if conditions.is_empty() {
+            return Err(self.unexpected(TokenKind::When));
+        }
+
+        let else_result = if self.consume(TokenKind::Else).is_some() {
+            Some(Box::new(self.parse_expression()?))
+        } else {
+            None
+        };
+
+        let end = self.expect(TokenKind::End)?.span.end;
+
+        Ok(Expression::Case {
+            span: Span::from(start..end),
+            operand,
+            conditions,
+            else_result,
+        })
+    }
+
+    // name AS (spec)
+    fn parse_named_window(&mut self) -> Result<NamedWindow, ParseError> {
+        let name_token = self.expect(TokenKind::Identifier)?;
+        let name = self.get_identifier(&name_token);
+        self.expect(TokenKind::As)?;
+        let spec = self.parse_window_spec()?;
+
+        Ok(NamedWindow {
+            span: Span::from(name_token.span.start..spec.span.end),
+            name,
+            spec,
+        })
     }
 
-    fn get_string_literal(&self, span: &Range<usize>) -> String {
-        let text = &self.input[span.clone()];
-        // remove surrounding quotes and trim whitespace
-        if text.starts_with('\'') && text.ends_with('\'') {
-            // TODO: handle escape sequences properly
-            text[1..text.len() - 1].to_string()
+    // ([base window] [PARTITION BY ...] [ORDER BY ...] [frame])
+    fn parse_window_spec(&mut self) -> Result<WindowSpec, ParseError> {
+        let start = self.expect(TokenKind::LParens)?.span.start;
+
+        let base = match self.current {
+            Some(ref token)
+                if token.kind == TokenKind::Identifier
+                    && !["partition", "rows", "range", "groups"]
+                        .iter()
+                        .any(|word| self.is_word(token, word)) =>
+            {
+                Some(self.parse_identifier()?)
+            }
+            _ => None,
+        };
+
+        let partition_by = if self.consume_word("partition").is_some() {
+            self.expect(TokenKind::By)?;
+            self.parse_expression_list()?
+        } else {
+            Vec::new()
+        };
+
+        let order_by = if self.consume(TokenKind::Order).is_some() {
+            self.expect(TokenKind::By)?;
+            self.parse_order_by_list()?
+        } else {
+            Vec::new()
+        };
+
+        let units = if self.consume_word("rows").is_some() {
+            Some(FrameUnits::Rows)
+        } else if self.consume_word("range").is_some() {
+            Some(FrameUnits::Range)
+        } else if self.consume_word("groups").is_some() {
+            Some(FrameUnits::Groups)
         } else {
-            text.to_string()
+            None
+        };
+
+        let frame = match units {
+            Some(units) if self.consume(TokenKind::Between).is_some() => {
+                let start = self.parse_frame_bound()?;
+                self.expect(TokenKind::And)?;
+                Some(WindowFrame {
+                    units,
+                    start,
+                    end: Some(self.parse_frame_bound()?),
+                })
+            }
+            Some(units) => Some(WindowFrame {
+                units,
+                start: self.parse_frame_bound()?,
+                end: None,
+            }),
+            None => None,
+        };
+
+        let end = self.expect(TokenKind::RParens)?.span.end;
+
+        Ok(WindowSpec {
+            span: Span::from(start..end),
+            base,
+            partition_by,
+            order_by,
+            frame,
+        })
+    }
+
+    // UNBOUNDED {PRECEDING | FOLLOWING}, CURRENT ROW or n {PRECEDING | FOLLOWING}
+    fn parse_frame_bound(&mut self) -> Result<FrameBound, ParseError> {
+        if self.consume_word("unbounded").is_some() {
+            if self.consume_word("preceding").is_some() {
+                return Ok(FrameBound::UnboundedPreceding);
+            }
+            self.expect_word("following")?;
+            return Ok(FrameBound::UnboundedFollowing);
+        }
+
+        if self.consume_word("current").is_some() {
+            self.expect_word("row")?;
+            return Ok(FrameBound::CurrentRow);
+        }
+
+        let offset = Box::new(self.parse_expression()?);
+        if self.consume_word("preceding").is_some() {
+            return Ok(FrameBound::Preceding(offset));
+        }
+        self.expect_word("following")?;
+        Ok(FrameBound::Following(offset))
+    }
+
+    pub fn get_identifier_text(&self, token: &Token) -> String {
+        self.get_identifier(token).value
+    }
+
+    // strip the quotes off a quoted identifier (e.g. [column 1] -> column 1),
+    // remembering how it was quoted
+    fn get_identifier(&self, token: &Token) -> Identifier {
+        let text = &self.input[token.span.clone()];
+
+        let (quote_style, close) = match text.chars().next() {
+            Some('"') => (QuoteStyle::DoubleQuote, '"'),
+            Some('`') => (QuoteStyle::Backtick, '`'),
+            Some('[') => (QuoteStyle::Bracket, ']'),
+            _ => return Identifier::new(text),
+        };
+
+        // a doubled closing quote inside the name is an escaped one
+        let body = &text[1..text.len() - 1];
+        let doubled = format!("{}{}", close, close);
+        Identifier::quoted(body.replace(&doubled, &close.to_string()), quote_style)
+    }
+
+    fn get_string_literal(&self, span: &Range<usize>) -> LiteralValue {
+        let raw = &self.input[span.clone()];
+
+        // split off the prefix, if any, and the surrounding quotes. MySQL
+        // strings can be "double quoted" too
+        let (kind, quoted) = match raw.as_bytes()[0] {
+            b'n' | b'N' => (StringKind::National, &raw[1..]),
+            b'e' | b'E' => (StringKind::Escape, &raw[1..]),
+            _ => (StringKind::Regular, raw),
+        };
+        let quote = quoted.as_bytes()[0] as char;
+        let body = &quoted[1..quoted.len() - 1];
+
+        let mut value = String::with_capacity(body.len());
+        let mut chars = body.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                // doubled quotes stand for a single quote
+                c if c == quote => {
+                    chars.next();
+                    value.push(quote);
+                }
+                '\\' if kind == StringKind::Escape => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some(other) => value.push(other),
+                    None => {}
+                },
+                _ => value.push(c),
+            }
+        }
+
+        LiteralValue::String {
+            value,
+            raw: raw.to_string(),
+            kind,
         }
     }
 
@@ -428,3 +2574,97 @@ impl<'a> Parser<'a> {
         self.input[span.clone()].to_string()
     }
 }
+
+// `::` binds tighter than any binary or unary operator
+const CAST_PRECEDENCE: u8 = 10;
+
+// unreserved words that can come right after a table or a select item, so
+// they're never taken as an alias unless AS comes first
+const ALIAS_STOP_WORDS: &[&str] = &[
+    "set",
+    "using",
+    "output",
+    "values",
+    "returning",
+    "limit",
+    "offset",
+    "fetch",
+    "window",
+];
+
+// the binary operator a token stands for, if any
+fn binary_operator(kind: &TokenKind) -> Option<Operator> {
+    let op = match kind {
+        TokenKind::And => Operator::And,
+        TokenKind::Or => Operator::Or,
+        TokenKind::Equals => Operator::Equals,
+        TokenKind::NotEquals => Operator::NotEquals,
+        TokenKind::BangEquals => Operator::BangEquals,
+        TokenKind::LessThan => Operator::LessThan,
+        TokenKind::GreaterThan => Operator::GreaterThan,
+        TokenKind::LessEquals => Operator::LessEquals,
+        TokenKind::GreaterEquals => Operator::GreaterEquals,
+        TokenKind::NotLessThan => Operator::NotLessThan,
+        TokenKind::NotGreaterThan => Operator::NotGreaterThan,
+        TokenKind::Plus => Operator::Plus,
+        TokenKind::Minus => Operator::Minus,
+        TokenKind::Asterisk => Operator::Multiply,
+        TokenKind::Slash => Operator::Divide,
+        TokenKind::Percent => Operator::Modulo,
+        TokenKind::Concat => Operator::Concat,
+        TokenKind::Ampersand => Operator::BitwiseAnd,
+        TokenKind::Pipe => Operator::BitwiseOr,
+        TokenKind::Caret => Operator::BitwiseXor,
+        TokenKind::ShiftLeft => Operator::ShiftLeft,
+        TokenKind::ShiftRight => Operator::ShiftRight,
+        TokenKind::Arrow => Operator::JsonGet,
+        TokenKind::LongArrow => Operator::JsonGetText,
+        TokenKind::AtArrow => Operator::Contains,
+        TokenKind::ArrowAt => Operator::ContainedBy,
+        _ => return None,
+    };
+
+    Some(op)
+}
+
+// the operator between the column and value of a SET assignment
+fn assignment_operator(kind: &TokenKind) -> Option<AssignmentOperator> {
+    let op = match kind {
+        TokenKind::Equals => AssignmentOperator::Equals,
+        TokenKind::PlusEquals => AssignmentOperator::Plus,
+        TokenKind::MinusEquals => AssignmentOperator::Minus,
+        TokenKind::AsteriskEquals => AssignmentOperator::Multiply,
+        TokenKind::SlashEquals => AssignmentOperator::Divide,
+        TokenKind::PercentEquals => AssignmentOperator::Modulo,
+        TokenKind::AmpersandEquals => AssignmentOperator::BitwiseAnd,
+        TokenKind::PipeEquals => AssignmentOperator::BitwiseOr,
+        TokenKind::CaretEquals => AssignmentOperator::BitwiseXor,
+        _ => return None,
+    };
+
+    Some(op)
+}
+
+fn set_operator(kind: &TokenKind) -> Option<SetOperator> {
+    match kind {
+        TokenKind::Union => Some(SetOperator::Union),
+        TokenKind::Intersect => Some(SetOperator::Intersect),
+        TokenKind::Except => Some(SetOperator::Except),
+        _ => None,
+    }
+}
+
+// the comments a statement carries, whatever kind of statement it is
+fn statement_comments(statement: &mut AST) -> &mut Vec<Comment> {
+    match statement {
+        AST::Query(query) => &mut query.comments,
+        AST::Insert(insert) => &mut insert.comments,
+        AST::Update(update) => &mut update.comments,
+        AST::Delete(delete) => &mut delete.comments,
+        AST::Merge(merge) => &mut merge.comments,
+        AST::CreateTable(create) => &mut create.comments,
+        AST::AlterTable(alter) => &mut alter.comments,
+        AST::Drop(drop) => &mut drop.comments,
+        AST::Truncate(truncate) => &mut truncate.comments,
+    }
+}
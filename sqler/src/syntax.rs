@@ -0,0 +1,166 @@
+use crate::config::SqlDialect;
+use crate::lexer::{Diagnostic, Lexer, Token, TokenKind};
+use std::ops::Range;
+
+// lossless token stream over the input, grouped into nodes only at parentheses.
+// every byte of the input, whitespace and comments included, belongs to exactly
+// one token, so the original text can always be recovered from it. the parser
+// reads its tokens from here but doesn't build on the grouping; instead the
+// spans in the AST point into the same text, and `covering_node` gets from any
+// of them back to the tokens it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxTree<'a> {
+    input: &'a str,
+    root: SyntaxNode,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxNodeKind {
+    Root,
+    // a `( ... )` group, parens included. an unclosed group runs to the end of input
+    Parenthesized,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxNode {
+    pub kind: SyntaxNodeKind,
+    pub span: Range<usize>,
+    pub children: Vec<SyntaxElement>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(Token),
+}
+
+impl<'a> SyntaxTree<'a> {
+    pub fn parse(input: &'a str) -> Self {
+        Self::parse_with_dialect(input, SqlDialect::Generic)
+    }
+
+    pub fn parse_with_dialect(input: &'a str, dialect: SqlDialect) -> Self {
+        let mut lexer = Lexer::with_dialect(input, dialect);
+
+        // open nodes, innermost last. the root is always at the bottom
+        let mut stack = vec![SyntaxNode::new(SyntaxNodeKind::Root, 0)];
+
+        while let Some(token) = lexer.next_token() {
+            match token.kind {
+                TokenKind::LParens => {
+                    let mut node = SyntaxNode::new(SyntaxNodeKind::Parenthesized, token.span.start);
+                    node.push(SyntaxElement::Token(token));
+                    stack.push(node);
+                }
+                TokenKind::RParens if stack.len() > 1 => {
+                    let mut node = stack.pop().unwrap();
+                    node.push(SyntaxElement::Token(token));
+                    stack.last_mut().unwrap().push(SyntaxElement::Node(node));
+                }
+                _ => stack.last_mut().unwrap().push(SyntaxElement::Token(token)),
+            }
+        }
+
+        // close any groups still open at the end of input
+        while stack.len() > 1 {
+            let node = stack.pop().unwrap();
+            stack.last_mut().unwrap().push(SyntaxElement::Node(node));
+        }
+
+        let mut root = stack.pop().unwrap();
+        root.span = 0..input.len();
+
+        Self {
+            input,
+            root,
+            diagnostics: lexer.diagnostics().to_vec(),
+        }
+    }
+
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+
+    // lexical problems in the input. the offending text is kept in `Error` tokens
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    // every token in source order, trivia included
+    pub fn tokens(&self) -> Vec<&Token> {
+        self.root.tokens()
+    }
+
+    pub fn token_text(&self, token: &Token) -> &'a str {
+        &self.input[token.span.clone()]
+    }
+
+    // the innermost node containing all of `span`, e.g. the group around an
+    // AST node's span. the root if no parenthesized group does
+    pub fn covering_node(&self, span: Range<usize>) -> &SyntaxNode {
+        let mut node = &self.root;
+
+        while let Some(child) = node.children.iter().find_map(|child| match child {
+            SyntaxElement::Node(child)
+                if child.span.start <= span.start && span.end <= child.span.end =>
+            {
+                Some(child)
+            }
+            _ => None,
+        }) {
+            node = child;
+        }
+
+        node
+    }
+
+    // rebuild the source text from the tree. always equal to the input
+    pub fn text(&self) -> String {
+        self.tokens()
+            .into_iter()
+            .map(|token| self.token_text(token))
+            .collect()
+    }
+}
+
+impl SyntaxNode {
+    fn new(kind: SyntaxNodeKind, start: usize) -> Self {
+        Self {
+            kind,
+            span: start..start,
+            children: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, element: SyntaxElement) {
+        self.span.end = element.span().end;
+        self.children.push(element);
+    }
+
+    pub fn tokens(&self) -> Vec<&Token> {
+        let mut tokens = Vec::new();
+
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+
+        tokens
+    }
+}
+
+impl SyntaxElement {
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            SyntaxElement::Node(node) => node.span.clone(),
+            SyntaxElement::Token(token) => token.span.clone(),
+        }
+    }
+}
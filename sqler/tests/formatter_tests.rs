@@ -1,4 +1,5 @@
 use sqler::config::{Config, LineBreakStyle, SqlDialect};
+use sqler::error::ParseError;
 use sqler::formatter::format_sql;
 
 mod tests {
@@ -75,4 +76,984 @@ FROM users";
         let result = format_sql(sql, &default_config()).unwrap();
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn test_leading_comment() {
+        let sql = "-- active users\nselect id, name from users";
+        let expected = "\
+-- active users
+SELECT id,
+       name
+FROM users";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_trailing_comments() {
+        let sql = "SELECT id, -- primary key\nname -- display name\nFROM users -- all of them";
+        let expected = "\
+SELECT id, -- primary key
+       name -- display name
+FROM users -- all of them";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_comments_between_clauses() {
+        let sql = "SELECT id,\n-- the name\nname FROM users\n/* only the first */\nWHERE id = 1";
+        let expected = "\
+SELECT id,
+       -- the name
+       name
+FROM users
+/* only the first */
+WHERE id = 1";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_comments_between_conditions() {
+        let sql = "SELECT id FROM users WHERE x = 1 -- c\n AND y = 2\n-- own line\nOR z = 3";
+        let expected = "\
+SELECT id
+FROM users
+WHERE x = 1 -- c
+  AND y = 2
+    -- own line
+   OR z = 3";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_lexical_error_after_statement() {
+        let sql = "SELECT id FROM users $";
+        let result = format_sql(sql, &default_config());
+
+        assert!(matches!(result, Err(ParseError::Lexical { span, .. }) if span == (21..22)));
+    }
+
+    #[test]
+    fn test_literals_keep_their_spelling() {
+        let sql = "select id from t where a = 'it''s' group by .5, 1E10, 0xFF, N'x', X'0A', null";
+        let expected = "\
+SELECT id
+FROM t
+WHERE a = 'it''s'
+GROUP BY .5, 1E10, 0xFF, N'x', X'0A', NULL";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_quoted_identifiers() {
+        let sql =
+            r#"select [Employee Id] as [Id]]x], "name" from [HR Schema].[Employee Table] emp"#;
+        let expected = r#"SELECT [Employee Id] AS [Id]]x],
+       "name"
+FROM [HR Schema].[Employee Table] AS emp"#;
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_operators_round_trip() {
+        let sql = "select price*qty as total, a||b, c % 2, data->>'name', d::text \
+                   from t where a<>1 group by a != b, x @> y, f & 3, g -> 'k'";
+        let expected = "\
+SELECT price * qty     AS total,
+       a || b,
+       c % 2,
+       data ->> 'name',
+       d::text
+FROM t
+WHERE a <> 1
+GROUP BY a != b, x @> y, f & 3, g -> 'k'";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_where_with_logical_operators() {
+        let sql = "select id from orders where order_date >= '2024-01-01' and status = 'completed' or (a = 1 and not b)";
+        let expected = "\
+SELECT id
+FROM orders
+WHERE order_date >= '2024-01-01'
+  AND status = 'completed'
+   OR (a = 1 AND NOT b)";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_expressions_keep_parentheses() {
+        let sql = "select (a+b)*c as x, -price, a+b*c from t";
+        let expected = "\
+SELECT (a + b) * c AS x,
+       -price,
+       a + b * c
+FROM t";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_nested_unary_operators_stay_apart() {
+        let sql = "select - -1, -(+2), + -x, -~y";
+        let expected = "\
+SELECT - -1,
+       -(+2),
+       + -x,
+       - ~y";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+        // the output must still parse to the same thing
+        assert_eq!(expected, format_sql(&result, &default_config()).unwrap());
+    }
+
+    #[test]
+    fn test_join_layout() {
+        let sql = "select name, title from products p left join categories as c on category_id = cid and active = 1 inner join brands b using (brand_id)";
+        let expected = "\
+SELECT name,
+       title
+FROM products AS p
+     LEFT JOIN categories AS c
+            ON category_id = cid
+           AND active = 1
+     INNER JOIN brands AS b
+          USING (brand_id)";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_parenthesized_joins() {
+        let sql = "select * from t join (u join v on u.id = v.id) on t.id = u.id";
+        let expected = "\
+SELECT *
+FROM t
+     JOIN (
+        u
+        JOIN v
+          ON u.id = v.id
+    )
+       ON t.id = u.id";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+
+        let sql = "select * from (t left join u using (id)) cross join w";
+        let expected = "\
+SELECT *
+FROM (
+        t
+        LEFT JOIN u
+            USING (id)
+    )
+     CROSS JOIN w";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_comma_separated_from() {
+        let sql = "select * from a, b -- second\n cross join c, d";
+        let expected = "\
+SELECT *
+FROM a,
+     b -- second
+     CROSS JOIN c,
+     d";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_trailing_clauses() {
+        let sql = "select category_id, order_count as total_orders from products left join order_items on product_id = item_product_id group by category_id having order_count > 100 and category_id <> 3 order by total_orders desc nulls last, category_id limit 10 offset 20";
+        let expected = "\
+SELECT category_id,
+       order_count AS total_orders
+FROM products
+     LEFT JOIN order_items
+            ON product_id = item_product_id
+GROUP BY category_id
+HAVING order_count > 100
+   AND category_id <> 3
+ORDER BY total_orders DESC NULLS LAST, category_id
+LIMIT 10
+OFFSET 20";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_offset_fetch() {
+        let sql = "select id from t order by id offset 10 rows -- skip\nfetch next 5 rows only";
+        let expected = "\
+SELECT id
+FROM t
+ORDER BY id
+OFFSET 10 ROWS -- skip
+FETCH NEXT 5 ROWS ONLY";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_subquery_layout() {
+        let sql = "select order_id, (select total from order_details where order_id = oid) as total_amount from (select order_id, status from orders) as o where order_id in (select order_id from orders where status = 'cancelled' and order_date < '2024-01-01') and not exists (select id from refunds)";
+        let expected = "\
+SELECT order_id,
+       (
+           SELECT total
+           FROM order_details
+           WHERE order_id = oid
+       ) AS total_amount
+FROM (
+        SELECT order_id,
+               status
+        FROM orders
+    ) AS o
+WHERE order_id IN (
+        SELECT order_id
+        FROM orders
+        WHERE status = 'cancelled'
+          AND order_date < '2024-01-01'
+    )
+  AND NOT EXISTS (
+        SELECT id
+        FROM refunds
+    )";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_subquery_with_narrow_indent() {
+        let config = Config {
+            indent_width: 2,
+            ..default_config()
+        };
+        let sql = "select name from employees where department_id in (select id from departments where location = 'HQ') and hire_date >= '2023-01-01'";
+        let expected = "\
+SELECT name
+FROM employees
+WHERE department_id IN (
+    SELECT id
+    FROM departments
+    WHERE location = 'HQ'
+  )
+  AND hire_date >= '2023-01-01'";
+
+        let result = format_sql(sql, &config).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_with_clause_layout() {
+        let config = Config {
+            indent_width: 2,
+            ..default_config()
+        };
+        let sql = "-- pipeline\nwith monthly_sales as (select month, total_amount as revenue from orders group by month), avg_sales (average) as materialized (select revenue from monthly_sales) select month, revenue from monthly_sales cross join avg_sales where revenue > average";
+        let expected = "\
+-- pipeline
+WITH monthly_sales AS (
+  SELECT month,
+         total_amount AS revenue
+  FROM orders
+  GROUP BY month
+),
+avg_sales (average) AS MATERIALIZED (
+  SELECT revenue
+  FROM monthly_sales
+)
+SELECT month,
+       revenue
+FROM monthly_sales
+     CROSS JOIN avg_sales
+WHERE revenue > average";
+
+        let result = format_sql(sql, &config).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_recursive_with() {
+        let sql = "with recursive t (n) as (select n from seed) select n from t";
+        let expected = "\
+WITH RECURSIVE t (n) AS (
+    SELECT n
+    FROM seed
+)
+SELECT n
+FROM t";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_union_layout() {
+        let sql = "select name, 'Customer' as type from customers where status = 'active' union select name, 'Employee' as type from employees union all select name, kind from vendors order by name limit 10";
+        let expected = "\
+SELECT name,
+       'Customer' AS type
+FROM customers
+WHERE status = 'active'
+UNION
+SELECT name,
+       'Employee' AS type
+FROM employees
+UNION ALL
+SELECT name,
+       kind
+FROM vendors
+ORDER BY name
+LIMIT 10";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_parenthesized_set_operands() {
+        let sql = "(select id from a order by id limit 1) except (select id from b) -- done";
+        let expected = "\
+(
+    SELECT id
+    FROM a
+    ORDER BY id
+    LIMIT 1
+)
+EXCEPT
+(
+    SELECT id
+    FROM b
+) -- done";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_aggregates_in_sample_layout() {
+        let config = Config {
+            indent_width: 2,
+            ..default_config()
+        };
+        let sql = "SELECT p.category_id, c.category_name, COUNT(o.order_id) AS total_orders, SUM(o.quantity * p.price) AS revenue FROM products AS p LEFT JOIN categories AS c ON p.category_id = c.id LEFT JOIN order_items AS o ON p.product_id = o.product_id GROUP BY p.category_id, c.category_name HAVING COUNT(o.order_id) > 100 ORDER BY revenue DESC LIMIT 10";
+        let expected = "\
+SELECT p.category_id,
+       c.category_name,
+       COUNT(o.order_id)         AS total_orders,
+       SUM(o.quantity * p.price) AS revenue
+FROM products AS p
+     LEFT JOIN categories AS c
+            ON p.category_id = c.id
+     LEFT JOIN order_items AS o
+            ON p.product_id = o.product_id
+GROUP BY p.category_id, c.category_name
+HAVING COUNT(o.order_id) > 100
+ORDER BY revenue DESC
+LIMIT 10";
+
+        let result = format_sql(sql, &config).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_aggregate_modifiers() {
+        let sql = "select count(*), count(distinct customer_id), percentile_cont(0.5) within group (order by price desc) as median, string_agg(name, ', ' order by name) filter (where active) from t";
+        let expected = "\
+SELECT count(*),
+       count(DISTINCT customer_id),
+       percentile_cont(0.5) WITHIN GROUP (ORDER BY price DESC)    AS median,
+       string_agg(name, ', ' ORDER BY name) FILTER (WHERE active)
+FROM t";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_window_functions() {
+        let sql = "select id, row_number() over (partition by dept order by salary desc) as rn, sum(amount) over w as running from emp window w as (order by id rows between unbounded preceding and current row)";
+        let expected = "\
+SELECT id,
+       row_number() OVER (PARTITION BY dept ORDER BY salary DESC) AS rn,
+       sum(amount) OVER w                                         AS running
+FROM emp
+WINDOW w AS (ORDER BY id ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW)";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_long_window_breaks_across_lines() {
+        let sql = "SELECT department_id, SUM(total_amount) OVER (PARTITION BY department_id, region_id ORDER BY order_date ROWS BETWEEN 3 PRECEDING AND 1 FOLLOWING) AS moving_total FROM orders";
+        let expected = "\
+SELECT department_id,
+       SUM(total_amount) OVER (
+           PARTITION BY department_id, region_id
+           ORDER BY order_date
+           ROWS BETWEEN 3 PRECEDING AND 1 FOLLOWING
+       ) AS moving_total
+FROM orders";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_short_case_stays_inline() {
+        let sql = "select id, case when active then 'yes' else 'no' end as flag from t";
+        let expected = "\
+SELECT id,
+       CASE WHEN active THEN 'yes' ELSE 'no' END AS flag
+FROM t";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_long_case_aligns_when_and_then() {
+        let config = Config {
+            indent_width: 2,
+            max_line_length: 60,
+            ..default_config()
+        };
+        let sql = "SELECT CASE WHEN s.total_sold > 1000 THEN p.price * 1.1 WHEN s.total_sold < 100 THEN p.price * 0.9 ELSE p.price END AS price, CASE grade WHEN 'A' THEN 4 WHEN 'B' THEN 3 END AS points FROM products AS p";
+        let expected = "\
+SELECT CASE
+         WHEN s.total_sold > 1000 THEN p.price * 1.1
+         WHEN s.total_sold < 100  THEN p.price * 0.9
+         ELSE p.price
+       END AS price,
+       CASE grade WHEN 'A' THEN 4 WHEN 'B' THEN 3 END AS points
+FROM products AS p";
+
+        let result = format_sql(sql, &config).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_casts() {
+        let sql = "select cast(price as decimal(10,2)) as price, try_cast(note as varchar(max)), convert(varchar(10), created_at, 120), total::numeric(12, 2), at::timestamp with time zone from t";
+        let expected = "\
+SELECT CAST(price AS decimal(10, 2))         AS price,
+       TRY_CAST(note AS varchar(MAX)),
+       CONVERT(varchar(10), created_at, 120),
+       total::numeric(12, 2),
+       at::timestamp WITH TIME ZONE
+FROM t";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_arrays_in_generic() {
+        let sql = "select x::int[], cast(y as varchar(10)[3]), tags[i + 1] as tag from t";
+        let expected = "\
+SELECT x::int[],
+       CAST(y AS varchar(10)[3]),
+       tags[i + 1]               AS tag
+FROM t";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_predicates() {
+        let sql = "select a from t where a between 1 and 10 and b not like 'x!%' escape '!' and c is not null and d is distinct from e and f not in (1, 2)";
+        let expected = "\
+SELECT a
+FROM t
+WHERE a BETWEEN 1 AND 10
+  AND b NOT LIKE 'x!%' ESCAPE '!'
+  AND c IS NOT NULL
+  AND d IS DISTINCT FROM e
+  AND f NOT IN (1, 2)";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_long_in_list_wraps() {
+        let config = Config {
+            indent_width: 2,
+            max_line_length: 60,
+            ..default_config()
+        };
+        let sql = "SELECT id FROM orders WHERE status IN ('pending', 'processing', 'shipped', 'delivered', 'cancelled', 'returned') AND id > 0";
+        let expected = "\
+SELECT id
+FROM orders
+WHERE status IN (
+    'pending', 'processing', 'shipped', 'delivered',
+    'cancelled', 'returned'
+  )
+  AND id > 0";
+
+        let result = format_sql(sql, &config).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_multi_part_names() {
+        let sql = "select o.id, srv.sales.dbo.orders.total, c.* from srv.sales.dbo.orders as o join [my-project].dataset.customers c on c.id = o.customer_id";
+        let expected = "\
+SELECT o.id,
+       srv.sales.dbo.orders.total,
+       c.*
+FROM srv.sales.dbo.orders AS o
+     JOIN [my-project].dataset.customers AS c
+       ON c.id = o.customer_id";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_select_quantifiers() {
+        let sql = "select distinct city, state as st from t";
+        let expected = "\
+SELECT DISTINCT city,
+                state AS st
+FROM t";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+
+        let sql = "select distinct on (customer_id) customer_id, order_date from orders order by customer_id, order_date desc";
+        let expected = "\
+SELECT DISTINCT ON (customer_id) customer_id,
+                                 order_date
+FROM orders
+ORDER BY customer_id, order_date DESC";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_select_without_from() {
+        let result = format_sql("select 1", &default_config()).unwrap();
+        assert_eq!("SELECT 1", result);
+
+        let result = format_sql("select getdate() as now, 1 as one", &default_config()).unwrap();
+        assert_eq!("SELECT getdate() AS now,\n       1         AS one", result);
+    }
+
+    #[test]
+    fn test_insert_values_align_by_column() {
+        let sql = "insert into users (id, name, email) values (1, 'alice', 'a@example.com'), (20, 'bob', null), (300, 'carol', 'c@example.com') returning id";
+        let expected = "\
+INSERT INTO users (id, name, email)
+VALUES (1,   'alice', 'a@example.com'),
+       (20,  'bob',   NULL),
+       (300, 'carol', 'c@example.com')
+RETURNING id";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_insert_select_in_sample_layout() {
+        let config = Config {
+            indent_width: 2,
+            max_line_length: 80,
+            ..default_config()
+        };
+        let sql = "INSERT INTO monthly_reports (month, total_sales, avg_order_value, customer_count) SELECT DATE_TRUNC('month', order_date) AS month, SUM(total_amount) AS total_sales FROM orders";
+        let expected = "\
+INSERT INTO monthly_reports (
+    month,
+    total_sales,
+    avg_order_value,
+    customer_count
+  )
+SELECT DATE_TRUNC('month', order_date) AS month,
+       SUM(total_amount)               AS total_sales
+FROM orders";
+
+        let result = format_sql(sql, &config).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_upserts() {
+        let sql = "insert into t (id, n) values (1, 2) on conflict (id) do update set n = excluded.n, m = 1 where t.n < 5";
+        let expected = "\
+INSERT INTO t (id, n)
+VALUES (1, 2)
+ON CONFLICT (id) DO UPDATE
+SET n = excluded.n,
+    m = 1
+WHERE t.n < 5";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+
+        let sql = "insert t (a) output inserted.id values (1) on duplicate key update a = values(a), b = b + 1";
+        let expected = "\
+INSERT INTO t (a)
+OUTPUT inserted.id
+VALUES (1)
+ON DUPLICATE KEY UPDATE a = values(a),
+                        b = b + 1";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_update_aligns_assignments() {
+        let sql = "update users set name = 'a', updated_at = now(), id = id + 1 where id = 1 and active returning id";
+        let expected = "\
+UPDATE users
+SET name       = 'a',
+    updated_at = now(),
+    id         = id + 1
+WHERE id = 1
+  AND active
+RETURNING id";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_update_compound_assignments() {
+        let sql = "update t set total += amount, flags |= 4, name = 'a' where id = 1";
+        let expected = "\
+UPDATE t
+SET total += amount,
+    flags |= 4,
+    name  = 'a'
+WHERE id = 1";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_update_joins() {
+        let sql = "update t set a = 1 output inserted.a, deleted.a from t join u on t.id = u.id where u.x = 2";
+        let expected = "\
+UPDATE t
+SET a = 1
+OUTPUT inserted.a,
+       deleted.a
+FROM t
+     JOIN u
+       ON t.id = u.id
+WHERE u.x = 2";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+
+        let sql = "update t1 join t2 on t1.id = t2.id set t1.name = t2.name, t1.n = 0";
+        let expected = "\
+UPDATE t1
+       JOIN t2
+         ON t1.id = t2.id
+SET t1.name = t2.name,
+    t1.n    = 0";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_update_in_sample_layout() {
+        let config = Config {
+            indent_width: 2,
+            max_line_length: 80,
+            ..default_config()
+        };
+        let sql = "UPDATE products AS p SET price = CASE WHEN s.total_sold > 1000 THEN p.price * 1.1 WHEN s.total_sold < 100 THEN p.price * 0.9 ELSE p.price END FROM (SELECT product_id, SUM(quantity) AS total_sold FROM order_items GROUP BY product_id) AS s WHERE p.product_id = s.product_id";
+        let expected = "\
+UPDATE products AS p
+SET price = CASE
+    WHEN s.total_sold > 1000 THEN p.price * 1.1
+    WHEN s.total_sold < 100  THEN p.price * 0.9
+    ELSE p.price
+  END
+FROM (
+    SELECT product_id,
+           SUM(quantity) AS total_sold
+    FROM order_items
+    GROUP BY product_id
+  ) AS s
+WHERE p.product_id = s.product_id";
+
+        let result = format_sql(sql, &config).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_delete() {
+        let sql = "delete from users where id = 1 and active = false returning *";
+        let expected = "\
+DELETE FROM users
+WHERE id = 1
+  AND active = FALSE
+RETURNING *";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+
+        let sql = "delete top (10) from logs output deleted.id where created < '2020-01-01'";
+        let expected = "\
+DELETE TOP (10) FROM logs
+OUTPUT deleted.id
+WHERE created < '2020-01-01'";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_delete_joins() {
+        let sql =
+            "delete o from orders o join customers c on o.customer_id = c.id where c.banned = 1";
+        let expected = "\
+DELETE o
+FROM orders AS o
+     JOIN customers AS c
+       ON o.customer_id = c.id
+WHERE c.banned = 1";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+
+        let sql = "delete from orders o using customers c, regions r where o.customer_id = c.id";
+        let expected = "\
+DELETE FROM orders AS o
+USING customers AS c,
+      regions AS r
+WHERE o.customer_id = c.id";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_merge() {
+        let sql = "merge into dbo.customers as t using (select id, name, email from staging.customers where batch = 7) as s on t.id = s.id and t.region = s.region \
+            when matched and s.deleted = 1 then delete \
+            when matched then update set name = s.name, email_address = s.email \
+            when not matched by target then insert (id, name, email_address) values (s.id, s.name, s.email) \
+            when not matched by source then delete \
+            output $action, inserted.id";
+        let expected = "\
+MERGE INTO dbo.customers AS t
+USING (
+        SELECT id,
+               name,
+               email
+        FROM staging.customers
+        WHERE batch = 7
+    ) AS s
+   ON t.id = s.id
+  AND t.region = s.region
+WHEN MATCHED AND s.deleted = 1 THEN
+    DELETE
+WHEN MATCHED THEN
+    UPDATE SET name          = s.name,
+               email_address = s.email
+WHEN NOT MATCHED BY TARGET THEN
+    INSERT (id, name, email_address)
+    VALUES (s.id, s.name, s.email)
+WHEN NOT MATCHED BY SOURCE THEN
+    DELETE
+OUTPUT $action,
+       inserted.id";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_merge_insert_default_values() {
+        let sql = "MERGE t USING s ON t.id = s.id WHEN NOT MATCHED THEN INSERT DEFAULT VALUES";
+        let expected = "\
+MERGE INTO t
+USING s
+   ON t.id = s.id
+WHEN NOT MATCHED THEN
+    INSERT DEFAULT VALUES";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_create_table_in_sample_layout() {
+        let config = Config {
+            indent_width: 2,
+            max_line_length: 80,
+            ..default_config()
+        };
+        let sql = "CREATE TABLE employee_records (id SERIAL PRIMARY KEY, employee_id VARCHAR(10) NOT NULL UNIQUE, email VARCHAR(100) CHECK (email LIKE '%@%'), salary DECIMAL(10, 2) CHECK (salary > 0), department_id INTEGER REFERENCES departments(id), created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, first_name VARCHAR(50), CONSTRAINT name_check CHECK (first_name != '' AND email != ''))";
+        let expected = "\
+CREATE TABLE employee_records (
+  id            SERIAL         PRIMARY KEY,
+  employee_id   VARCHAR(10)    NOT NULL UNIQUE,
+  email         VARCHAR(100)   CHECK (email LIKE '%@%'),
+  salary        DECIMAL(10, 2) CHECK (salary > 0),
+  department_id INTEGER        REFERENCES departments(id),
+  created_at    TIMESTAMP      DEFAULT CURRENT_TIMESTAMP,
+  first_name    VARCHAR(50),
+  CONSTRAINT name_check CHECK (
+    first_name != ''
+    AND email != ''
+  )
+)";
+
+        let result = format_sql(sql, &config).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_create_table_constraints() {
+        let sql = "create temp table if not exists a.b (id bigint generated always as identity, n int identity(1,1) not null, -- counter\n v numeric generated always as (n * 2) stored, p int constraint fk references t (id) on delete cascade, primary key (id, n), unique (p), check (n > 0))";
+        let expected = "\
+CREATE TEMPORARY TABLE IF NOT EXISTS a.b (
+    id bigint  GENERATED ALWAYS AS IDENTITY,
+    n  int     IDENTITY(1, 1) NOT NULL, -- counter
+    v  numeric GENERATED ALWAYS AS (n * 2) STORED,
+    p  int     CONSTRAINT fk REFERENCES t(id) ON DELETE CASCADE,
+    PRIMARY KEY (id, n),
+    UNIQUE (p),
+    CHECK (n > 0)
+)";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_alter_table_in_sample_layout() {
+        let config = Config {
+            indent_width: 2,
+            max_line_length: 80,
+            ..default_config()
+        };
+        let sql = "ALTER TABLE products ADD COLUMN description TEXT, ADD COLUMN last_modified_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, ADD CONSTRAINT price_check CHECK (price >= 0), DROP COLUMN outdated_field, ALTER COLUMN category_id SET NOT NULL, RENAME COLUMN product_name TO name;";
+        let expected = "\
+ALTER TABLE products
+  ADD COLUMN     description      TEXT,
+  ADD COLUMN     last_modified_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  ADD CONSTRAINT price_check      CHECK (price >= 0),
+  DROP COLUMN    outdated_field,
+  ALTER COLUMN   category_id      SET NOT NULL,
+  RENAME COLUMN  product_name     TO name;";
+
+        let result = format_sql(sql, &config).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_alter_table_actions() {
+        let sql = "alter table if exists t add primary key (id), drop constraint if exists c cascade, alter column n type bigint, alter column m set default 0, rename to u";
+        let expected = "\
+ALTER TABLE IF EXISTS t
+    ADD                         PRIMARY KEY (id),
+    DROP CONSTRAINT IF EXISTS c CASCADE,
+    ALTER COLUMN              n TYPE bigint,
+    ALTER COLUMN              m SET DEFAULT 0,
+    RENAME TO                 u";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_drop_and_truncate() {
+        let sql =
+            "drop table if exists a, b cascade; drop index ix on t; truncate c restart identity";
+        let expected = "\
+DROP TABLE IF EXISTS a, b CASCADE;
+
+DROP INDEX ix ON t;
+
+TRUNCATE TABLE c RESTART IDENTITY";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_migration_file() {
+        let sql = "-- migration 42\ncreate table t (id int primary key); -- table\n\n-- data\ninsert into t values (1);\nalter table t add column n int;\n-- done\n";
+        let expected = "\
+-- migration 42
+CREATE TABLE t (
+    id int PRIMARY KEY
+); -- table
+
+-- data
+INSERT INTO t
+VALUES (1);
+
+ALTER TABLE t
+    ADD COLUMN n int;
+-- done";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_trailing_tokens_are_rejected() {
+        assert!(format_sql("SELECT a FROM t b c", &default_config()).is_err());
+    }
+
+    #[test]
+    fn test_input_without_statements() {
+        for sql in ["", "  \n", ";"] {
+            assert_eq!(format_sql(sql, &default_config()).unwrap(), "", "{:?}", sql);
+        }
+
+        let sql = "-- only a comment
+
+/* and a block */ -- trailing
+";
+        let expected = "\
+-- only a comment
+/* and a block */ -- trailing";
+
+        let result = format_sql(sql, &default_config()).unwrap();
+        assert_eq!(expected, result);
+    }
 }
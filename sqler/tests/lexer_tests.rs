@@ -1,11 +1,278 @@
-use sqler::lexer::Lexer;
+use sqler::config::SqlDialect;
+use sqler::error::LexError;
+use sqler::lexer::{Lexer, TokenKind};
 
 mod tests {
     use super::*;
 
+    fn token_kinds(sql: &str) -> Vec<TokenKind> {
+        let mut lexer = Lexer::new(sql);
+        std::iter::from_fn(|| lexer.next_token())
+            .map(|token| token.kind)
+            .filter(|kind| !kind.is_trivia())
+            .collect()
+    }
+
     #[test]
     fn test_simple_select() {
         let sql = "select id, name from users";
+        assert_eq!(
+            token_kinds(sql),
+            vec![
+                TokenKind::Select,
+                TokenKind::Identifier,
+                TokenKind::Comma,
+                TokenKind::Identifier,
+                TokenKind::From,
+                TokenKind::Identifier,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_comment() {
+        let sql = "SELECT id -- the id\nFROM users";
+        let mut lexer = Lexer::new(sql);
+        let comment = std::iter::from_fn(|| lexer.next_token())
+            .find(|token| token.kind == TokenKind::LineComment)
+            .unwrap();
+
+        assert_eq!(&sql[comment.span], "-- the id");
+    }
+
+    #[test]
+    fn test_block_comment() {
+        let sql = "SELECT /* a\n ** multi-line */ id FROM users";
+        let mut lexer = Lexer::new(sql);
+        let comment = std::iter::from_fn(|| lexer.next_token())
+            .find(|token| token.kind == TokenKind::BlockComment)
+            .unwrap();
+
+        assert_eq!(&sql[comment.span], "/* a\n ** multi-line */");
+    }
+
+    #[test]
+    fn test_unexpected_character() {
+        let sql = "SELECT a $ b FROM t";
+        let mut lexer = Lexer::new(sql);
+        let tokens: Vec<_> = std::iter::from_fn(|| lexer.next_token()).collect();
+
+        let error = tokens
+            .iter()
+            .find(|token| token.kind == TokenKind::Error)
+            .unwrap();
+        assert_eq!(&sql[error.span.clone()], "$");
+
+        // lexing carries on past the error
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::Identifier);
+        assert_eq!(lexer.diagnostics().len(), 1);
+        assert_eq!(lexer.diagnostics()[0].error, LexError::UnexpectedCharacter);
+        assert_eq!(lexer.diagnostics()[0].span, 9..10);
+    }
+
+    #[test]
+    fn test_unterminated_string() {
+        let sql = "SELECT 'abc FROM t";
         let mut lexer = Lexer::new(sql);
+        let tokens: Vec<_> = std::iter::from_fn(|| lexer.next_token()).collect();
+
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::Error);
+        assert_eq!(tokens.last().unwrap().span, 7..sql.len());
+        assert_eq!(lexer.diagnostics()[0].error, LexError::UnterminatedString);
+    }
+
+    #[test]
+    fn test_unterminated_bracket_identifier() {
+        let sql = "SELECT [first name FROM t";
+        let mut lexer = Lexer::new(sql);
+        std::iter::from_fn(|| lexer.next_token()).for_each(drop);
+
+        assert_eq!(
+            lexer.diagnostics()[0].error,
+            LexError::UnterminatedIdentifier
+        );
+    }
+
+    #[test]
+    fn test_unterminated_block_comment() {
+        let sql = "SELECT a /* no end";
+        let mut lexer = Lexer::new(sql);
+        std::iter::from_fn(|| lexer.next_token()).for_each(drop);
+
+        assert_eq!(lexer.diagnostics()[0].error, LexError::UnterminatedComment);
+        assert_eq!(lexer.diagnostics()[0].span, 9..sql.len());
+    }
+
+    #[test]
+    fn test_merge_action_column() {
+        assert_eq!(
+            token_kinds("OUTPUT $action, $ACTION"),
+            vec![
+                TokenKind::Identifier,
+                TokenKind::Identifier,
+                TokenKind::Comma,
+                TokenKind::Identifier,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escaped_quotes_in_string() {
+        let sql = "'it''s' 'a'";
+        assert_eq!(token_kinds(sql), vec![TokenKind::String, TokenKind::String]);
+    }
+
+    #[test]
+    fn test_prefixed_strings() {
+        let sql = r"N'café' E'it\'s' n'x'";
+        assert_eq!(
+            token_kinds(sql),
+            vec![TokenKind::String, TokenKind::String, TokenKind::String]
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        for sql in ["42", "3.14", ".5", "1.", "1e10", "1.5E-3", "2e+8"] {
+            assert_eq!(token_kinds(sql), vec![TokenKind::Number], "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_hex_and_binary_numbers() {
+        assert_eq!(token_kinds("0x1F"), vec![TokenKind::HexNumber]);
+        assert_eq!(token_kinds("X'1f'"), vec![TokenKind::HexNumber]);
+        assert_eq!(token_kinds("0b101"), vec![TokenKind::BinaryNumber]);
+        assert_eq!(token_kinds("B'101'"), vec![TokenKind::BinaryNumber]);
+
+        // a prefix with no digits isn't a hex literal
+        assert_eq!(
+            token_kinds("0x"),
+            vec![TokenKind::Number, TokenKind::Identifier]
+        );
+    }
+
+    #[test]
+    fn test_quoted_identifiers_by_dialect() {
+        let sql = "\"a\" `b` [c]";
+        let kinds = |dialect| {
+            let mut lexer = Lexer::with_dialect(sql, dialect);
+            std::iter::from_fn(|| lexer.next_token())
+                .map(|token| token.kind)
+                .filter(|kind| !kind.is_trivia())
+                .collect::<Vec<_>>()
+        };
+
+        use TokenKind::{Error, Identifier, LBracket, RBracket, String};
+        assert_eq!(
+            kinds(SqlDialect::Generic),
+            vec![Identifier, Identifier, Identifier]
+        );
+        assert_eq!(kinds(SqlDialect::TSql), vec![Identifier, Error, Identifier]);
+        // postgres brackets are array bounds and subscripts, not quotes
+        assert_eq!(
+            kinds(SqlDialect::PostgreSql),
+            vec![Identifier, Error, LBracket, Identifier, RBracket]
+        );
+        assert_eq!(kinds(SqlDialect::MySql), vec![String, Identifier, Error]);
+    }
+
+    #[test]
+    fn test_brackets_after_names() {
+        use TokenKind::{DoubleColon, Identifier, LBracket, LParens, Number, RBracket, RParens};
+
+        // straight after a name or a closing paren, `[` starts an array bound
+        assert_eq!(
+            token_kinds("x::int[]"),
+            vec![Identifier, DoubleColon, Identifier, LBracket, RBracket]
+        );
+        assert_eq!(
+            token_kinds("varchar(10)[3]"),
+            vec![Identifier, LParens, Number, RParens, LBracket, Number, RBracket]
+        );
+        assert_eq!(
+            token_kinds("arr[1][i]"),
+            vec![Identifier, LBracket, Number, RBracket, LBracket, Identifier, RBracket]
+        );
+        // anywhere else it still quotes a name
+        assert_eq!(token_kinds("a [b]"), vec![Identifier, Identifier]);
+        assert_eq!(token_kinds("[a].[b]").len(), 3);
+
+        // t-sql has no arrays
+        let mut lexer = Lexer::with_dialect("a[b]", SqlDialect::TSql);
+        let tokens: Vec<_> = std::iter::from_fn(|| lexer.next_token()).collect();
+        assert_eq!(tokens.len(), 2);
+    }
+
+    #[test]
+    fn test_empty_quoted_identifiers() {
+        for sql in ["SELECT x AS []", "SELECT \"\"", "SELECT ``"] {
+            let mut lexer = Lexer::new(sql);
+            std::iter::from_fn(|| lexer.next_token()).for_each(drop);
+            assert_eq!(
+                lexer.diagnostics()[0].error,
+                LexError::EmptyIdentifier,
+                "{}",
+                sql
+            );
+        }
+
+        // an empty "string" is fine in mysql
+        let mut lexer = Lexer::with_dialect("SELECT \"\"", SqlDialect::MySql);
+        std::iter::from_fn(|| lexer.next_token()).for_each(drop);
+        assert!(lexer.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn test_operators() {
+        use TokenKind::*;
+        let sql =
+            "= <> != < > <= >= + - * / % || & | ^ ~ << >> :: -> ->> @> <@ += -= *= /= %= &= |= ^=";
+        assert_eq!(
+            token_kinds(sql),
+            vec![
+                Equals,
+                NotEquals,
+                BangEquals,
+                LessThan,
+                GreaterThan,
+                LessEquals,
+                GreaterEquals,
+                Plus,
+                Minus,
+                Asterisk,
+                Slash,
+                Percent,
+                Concat,
+                Ampersand,
+                Pipe,
+                Caret,
+                Tilde,
+                ShiftLeft,
+                ShiftRight,
+                DoubleColon,
+                Arrow,
+                LongArrow,
+                AtArrow,
+                ArrowAt,
+                PlusEquals,
+                MinusEquals,
+                AsteriskEquals,
+                SlashEquals,
+                PercentEquals,
+                AmpersandEquals,
+                PipeEquals,
+                CaretEquals,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_minus_is_not_a_comment() {
+        use TokenKind::*;
+        assert_eq!(
+            token_kinds("a-b--c\n/d/*e*/"),
+            vec![Identifier, Minus, Identifier, Slash, Identifier]
+        );
     }
 }
@@ -2,3 +2,4 @@
 mod formatter_tests;
 mod lexer_tests;
 mod parser_tests;
+mod syntax_tests;
@@ -1,19 +1,74 @@
-use sqler::ast::SelectItem;
+use sqler::ast::{
+    AlterColumnChange, AlterTableActionKind, AssignmentOperator, CastKind, ColumnConstraintKind,
+    CommentKind, ConflictAction, ConflictTarget, DataType, DropBehavior, Expression, FrameBound,
+    FrameUnits, Identifier, InsertSource, IsValue, Join, JoinConstraint, JoinKind, LikeKind,
+    LiteralValue, Materialization, MergeAction, MergeMatch, NullsOrder, ObjectName, ObjectType,
+    Operator, Quantifier, Query, QuoteStyle, ReferentialAction, ReturningKind, RowsKeyword,
+    SelectItem, SelectQuantifier, SelectStatement, SetExpression, SortDirection, StringKind,
+    TableConstraintKind, TableElement, TableExpression, TableReference, TimeZone, TypeArgument,
+    UnaryOperator, Upsert, Window, AST,
+};
+use sqler::config::SqlDialect;
+use sqler::error::{LexError, ParseError};
 use sqler::lexer::{Token, TokenKind};
 use sqler::parser::Parser;
+use sqler::syntax::SyntaxTree;
 
 mod tests {
     use super::*;
 
+    // parse `input` as one statement of the given kind, e.g.
+    // `parse_statement!(Insert, "INSERT ...")` gives the InsertStatement. a
+    // dialect other than the generic one can follow the input
+    macro_rules! parse_statement {
+        ($variant:ident, $input:expr $(,)?) => {
+            parse_statement!($variant, $input, SqlDialect::Generic)
+        };
+        ($variant:ident, $input:expr, $dialect:expr $(,)?) => {
+            match Parser::with_dialect($input, $dialect)
+                .parse_statement()
+                .unwrap()
+            {
+                AST::$variant(statement) => *statement,
+                other => panic!("expected {}, got {:?}", stringify!($variant), other),
+            }
+        };
+    }
+
+    // a name of unquoted parts from its dotted form, e.g. `dbo.users`
+    fn object_name(dotted: &str) -> ObjectName {
+        ObjectName(dotted.split('.').map(Identifier::new).collect())
+    }
+
+    // the single SELECT making up a query
+    fn select(query: &Query) -> &SelectStatement {
+        match &query.body {
+            SetExpression::Select(select) => select,
+            other => panic!("expected a SELECT, got {:?}", other),
+        }
+    }
+
+    // the leftmost table of the first FROM item
+    fn first_table(stmt: &SelectStatement) -> &TableReference {
+        let mut item = &stmt.from.as_ref().unwrap()[0];
+        loop {
+            match item {
+                TableExpression::Table(table) => return table,
+                TableExpression::Join(join) => item = &join.left,
+                TableExpression::Nested { expr, .. } => item = expr,
+                TableExpression::Derived { .. } => panic!("expected a table, got {:?}", item),
+            }
+        }
+    }
+
     #[test]
     fn test_simple_table() {
         let input = "SELECT * FROM users";
         let mut parser = Parser::new(input);
         let result = parser.parse_select().unwrap();
 
-        assert_eq!(result.from.name, "users");
-        assert_eq!(result.from.schema, None);
-        assert_eq!(result.from.alias, None);
+        assert_eq!(first_table(&result).name, object_name("users"));
+        assert_eq!(first_table(&result).alias, None);
     }
 
     #[test]
@@ -22,9 +77,11 @@ mod tests {
         let mut parser = Parser::new(input);
         let result = parser.parse_select().unwrap();
 
-        assert_eq!(result.from.name, "users");
-        assert_eq!(result.from.schema, Some("dbo".to_string()));
-        assert_eq!(result.from.alias, None);
+        assert_eq!(
+            first_table(&result).name,
+            ObjectName(vec![Identifier::new("dbo"), Identifier::new("users")])
+        );
+        assert_eq!(first_table(&result).alias, None);
     }
 
     #[test]
@@ -33,9 +90,14 @@ mod tests {
         let mut parser = Parser::new(input);
         let result = parser.parse_select().unwrap();
 
-        assert_eq!(result.from.name, "Employee Table");
-        assert_eq!(result.from.schema, Some("HR Schema".to_string()));
-        assert_eq!(result.from.alias, Some("emp".to_string()));
+        assert_eq!(
+            first_table(&result).name,
+            ObjectName(vec![
+                Identifier::quoted("HR Schema", QuoteStyle::Bracket),
+                Identifier::quoted("Employee Table", QuoteStyle::Bracket),
+            ])
+        );
+        assert_eq!(first_table(&result).alias, Some(Identifier::new("emp")));
     }
 
     #[test]
@@ -63,7 +125,7 @@ mod tests {
         let result = parser.parse_select().unwrap();
 
         assert_eq!(result.columns.len(), 1);
-        assert_eq!(result.from.name, "table1");
+        assert_eq!(first_table(&result).name, object_name("table1"));
         assert!(result.where_clause.is_none());
         assert!(result.group_by.is_none());
     }
@@ -76,7 +138,7 @@ mod tests {
 
         assert_eq!(result.columns.len(), 1);
         if let SelectItem::Expression { alias, .. } = &result.columns[0] {
-            assert_eq!(alias, &Some("alias1".to_string()));
+            assert_eq!(alias, &Some(Identifier::new("alias1")));
         } else {
             panic!("Expected Expression variant");
         }
@@ -119,4 +181,2411 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_comments_are_collected() {
+        let input = "-- leading\nSELECT id, /* inline */ name FROM users -- trailing";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_query().unwrap();
+
+        assert_eq!(select(&result).columns.len(), 2);
+        assert_eq!(result.comments.len(), 3);
+        assert_eq!(result.comments[0].text, "-- leading");
+        assert!(result.comments[0].own_line);
+        assert_eq!(result.comments[1].kind, CommentKind::Block);
+        assert!(!result.comments[1].own_line);
+        assert_eq!(result.comments[2].text, "-- trailing");
+        assert!(!result.comments[2].own_line);
+    }
+
+    #[test]
+    fn test_parse_from_syntax_tree() {
+        let input = "SELECT id\n  FROM users";
+        let tree = SyntaxTree::parse(input);
+        let mut parser = Parser::from_syntax_tree(&tree);
+        let result = parser.parse_select().unwrap();
+
+        // spans in the AST index into the text the tree reproduces
+        let span = result.from.unwrap()[0].span();
+        assert_eq!(&tree.text()[span.start..span.end], "users");
+    }
+
+    #[test]
+    fn test_lexical_error() {
+        let input = "SELECT id FROM users WHERE name = 'bob";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_select();
+
+        assert!(matches!(
+            result,
+            Err(ParseError::Lexical {
+                error: LexError::UnterminatedString,
+                ..
+            })
+        ));
+        assert_eq!(parser.diagnostics().len(), 1);
+    }
+
+    fn where_literal(input: &str) -> LiteralValue {
+        match where_condition(input) {
+            Expression::BinaryOperation { right, .. } => match *right {
+                Expression::Literal { value, .. } => value,
+                other => panic!("Expected literal, found {:?}", other),
+            },
+            other => panic!("Expected binary operation, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_escaped_string_literal() {
+        let value = where_literal("SELECT id FROM users WHERE name = 'O''Brien'");
+        assert_eq!(
+            value,
+            LiteralValue::String {
+                value: "O'Brien".to_string(),
+                raw: "'O''Brien'".to_string(),
+                kind: StringKind::Regular,
+            }
+        );
+    }
+
+    #[test]
+    fn test_national_and_escape_strings() {
+        let value = where_literal("SELECT id FROM users WHERE name = N'Zoë'");
+        assert!(matches!(
+            value,
+            LiteralValue::String { ref value, kind: StringKind::National, .. } if value == "Zoë"
+        ));
+
+        let value = where_literal(r"SELECT id FROM users WHERE name = E'tab\there\'s'");
+        assert!(matches!(
+            value,
+            LiteralValue::String { ref value, kind: StringKind::Escape, .. } if value == "tab\there's"
+        ));
+    }
+
+    #[test]
+    fn test_numeric_literals() {
+        assert_eq!(
+            where_literal("SELECT id FROM t WHERE x = 1.5e-3"),
+            LiteralValue::Number("1.5e-3".to_string())
+        );
+        assert_eq!(
+            where_literal("SELECT id FROM t WHERE x = 0x1F"),
+            LiteralValue::Hex("0x1F".to_string())
+        );
+        assert_eq!(
+            where_literal("SELECT id FROM t WHERE x = B'101'"),
+            LiteralValue::Binary("B'101'".to_string())
+        );
+        assert_eq!(
+            where_literal("SELECT id FROM t WHERE x = null"),
+            LiteralValue::Null
+        );
+        assert_eq!(
+            where_literal("SELECT id FROM t WHERE x = true"),
+            LiteralValue::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn test_identifier_quote_styles() {
+        let input = r#"SELECT "first ""name""", `last``name`, [odd]]name] FROM t"#;
+        let mut parser = Parser::new(input);
+        let result = parser.parse_select().unwrap();
+
+        let names: Vec<Identifier> = result
+            .columns
+            .into_iter()
+            .map(|item| match item {
+                SelectItem::Expression {
+                    expr: Expression::Column { mut name, .. },
+                    ..
+                } => name.0.pop().unwrap(),
+                other => panic!("Expected column, found {:?}", other),
+            })
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                Identifier::quoted("first \"name\"", QuoteStyle::DoubleQuote),
+                Identifier::quoted("last`name", QuoteStyle::Backtick),
+                Identifier::quoted("odd]name", QuoteStyle::Bracket),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dialect_quoting() {
+        // brackets are T-SQL only
+        let mut parser = Parser::with_dialect("SELECT [a] FROM t", SqlDialect::PostgreSql);
+        assert!(parser.parse_select().is_err());
+
+        let mut parser = Parser::with_dialect("SELECT [a] FROM t", SqlDialect::TSql);
+        assert!(parser.parse_select().is_ok());
+
+        // MySQL reads double quotes as a string
+        let mut parser =
+            Parser::with_dialect(r#"SELECT a FROM t WHERE b = "x""#, SqlDialect::MySql);
+        let result = parser.parse_select().unwrap();
+        match result.where_clause.unwrap().condition {
+            Expression::BinaryOperation { right, .. } => assert!(matches!(
+                *right,
+                Expression::Literal {
+                    value: LiteralValue::String { ref value, .. },
+                    ..
+                } if value == "x"
+            )),
+            other => panic!("Expected binary operation, found {:?}", other),
+        }
+    }
+
+    fn where_operator(input: &str) -> Operator {
+        match where_condition(input) {
+            Expression::BinaryOperation { op, .. } => op,
+            other => panic!("Expected binary operation, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_not_equals_spellings() {
+        assert_eq!(
+            where_operator("SELECT a FROM t WHERE a <> 1"),
+            Operator::NotEquals
+        );
+        assert_eq!(
+            where_operator("SELECT a FROM t WHERE a != 1"),
+            Operator::BangEquals
+        );
+        assert_eq!(
+            where_operator("SELECT a FROM t WHERE a >= 1"),
+            Operator::GreaterEquals
+        );
+    }
+
+    #[test]
+    fn test_operators_in_select_list() {
+        let input = "SELECT first_name || last_name AS full_name FROM t";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_select().unwrap();
+
+        match &result.columns[0] {
+            SelectItem::Expression { expr, alias, .. } => {
+                assert!(matches!(
+                    expr,
+                    Expression::BinaryOperation {
+                        op: Operator::Concat,
+                        ..
+                    }
+                ));
+                assert_eq!(alias, &Some(Identifier::new("full_name")));
+            }
+            other => panic!("Expected expression, found {:?}", other),
+        }
+    }
+
+    fn where_condition(input: &str) -> Expression {
+        let query = parse_statement!(Query, input);
+        select(&query).where_clause.clone().unwrap().condition
+    }
+
+    // render the tree with explicit grouping so precedence is easy to check
+    fn grouping(expr: &Expression) -> String {
+        match expr {
+            Expression::BinaryOperation {
+                left, op, right, ..
+            } => format!("({} {} {})", grouping(left), op, grouping(right)),
+            Expression::UnaryOperation { op, expr, .. } => format!("({} {})", op, grouping(expr)),
+            Expression::Nested { expr, .. } => format!("[{}]", grouping(expr)),
+            Expression::InSubquery { expr, .. } => format!("({} IN subquery)", grouping(expr)),
+            Expression::InList { expr, list, .. } => {
+                let list: Vec<String> = list.iter().map(grouping).collect();
+                format!("({} IN {})", grouping(expr), list.join(" "))
+            }
+            Expression::Between {
+                expr, low, high, ..
+            } => format!(
+                "({} BETWEEN {} {})",
+                grouping(expr),
+                grouping(low),
+                grouping(high)
+            ),
+            Expression::Like { expr, pattern, .. } => {
+                format!("({} LIKE {})", grouping(expr), grouping(pattern))
+            }
+            Expression::Is { expr, value, .. } => format!("({} IS {})", grouping(expr), value),
+            Expression::Column { name, .. } => name.to_string(),
+            Expression::Literal {
+                value: LiteralValue::Number(n),
+                ..
+            } => n.clone(),
+            other => panic!("Unexpected expression {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_logical_precedence() {
+        let expr = where_condition("SELECT a FROM t WHERE a = 1 OR b = 2 AND NOT c = 3");
+        assert_eq!(grouping(&expr), "((a = 1) OR ((b = 2) AND (NOT (c = 3))))");
+    }
+
+    #[test]
+    fn test_arithmetic_precedence() {
+        let expr = where_condition("SELECT a FROM t WHERE a + b * c - d / 2 > -e");
+        assert_eq!(grouping(&expr), "(((a + (b * c)) - (d / 2)) > (- e))");
+    }
+
+    #[test]
+    fn test_nested_parentheses() {
+        let input = "SELECT a FROM t WHERE ((a + b) * c) = 1";
+        let expr = where_condition(input);
+        assert_eq!(grouping(&expr), "([([(a + b)] * c)] = 1)");
+
+        match expr {
+            Expression::BinaryOperation { left, .. } => assert_eq!(left.span().start, 22),
+            other => panic!("Expected binary operation, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_compound_assignment_is_not_an_expression() {
+        for input in [
+            "SELECT a FROM t WHERE a += 1",
+            "SELECT a |= 2 FROM t",
+            "SELECT a FROM t WHERE b = 1 AND a -= 1",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_statements().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_unary_operators_in_select_list() {
+        let input = "SELECT -price, NOT flag, ~bits FROM t";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_select().unwrap();
+
+        let ops: Vec<UnaryOperator> = result
+            .columns
+            .into_iter()
+            .map(|item| match item {
+                SelectItem::Expression {
+                    expr: Expression::UnaryOperation { op, .. },
+                    ..
+                } => op,
+                other => panic!("Expected unary operation, found {:?}", other),
+            })
+            .collect();
+
+        assert_eq!(
+            ops,
+            vec![
+                UnaryOperator::Minus,
+                UnaryOperator::Not,
+                UnaryOperator::BitwiseNot
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unclosed_parenthesis() {
+        let mut parser = Parser::new("SELECT a FROM t WHERE (a = 1");
+        assert!(matches!(
+            parser.parse_select(),
+            Err(ParseError::UnexpectedEOF)
+        ));
+    }
+
+    fn parse_join(input: &str) -> Join {
+        let query = parse_statement!(Query, input);
+        match select(&query).from.clone().unwrap().into_iter().next() {
+            Some(TableExpression::Join(join)) => *join,
+            other => panic!("expected a join, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_join_kinds() {
+        let cases = [
+            ("JOIN", JoinKind::Join),
+            ("INNER JOIN", JoinKind::Inner),
+            ("LEFT JOIN", JoinKind::Left { outer: false }),
+            ("left outer join", JoinKind::Left { outer: true }),
+            ("RIGHT JOIN", JoinKind::Right { outer: false }),
+            ("RIGHT OUTER JOIN", JoinKind::Right { outer: true }),
+            ("FULL JOIN", JoinKind::Full { outer: false }),
+            ("FULL OUTER JOIN", JoinKind::Full { outer: true }),
+            ("CROSS JOIN", JoinKind::Cross),
+        ];
+
+        for (keyword, kind) in cases {
+            let join = parse_join(&format!("SELECT * FROM a {} b", keyword));
+            assert_eq!(join.kind, kind, "{}", keyword);
+            assert!(!join.natural);
+            assert!(join.constraint.is_none());
+        }
+    }
+
+    #[test]
+    fn test_join_on_and_using() {
+        let join = parse_join("SELECT * FROM orders o JOIN customers AS c ON customer_id = id");
+        match &join.right {
+            TableExpression::Table(table) => {
+                assert_eq!(table.name, object_name("customers"));
+                assert_eq!(table.alias, Some(Identifier::new("c")));
+            }
+            other => panic!("expected a table, got {:?}", other),
+        }
+        assert!(matches!(
+            join.constraint,
+            Some(JoinConstraint::On(Expression::BinaryOperation {
+                op: Operator::Equals,
+                ..
+            }))
+        ));
+
+        let join = parse_join("SELECT * FROM a NATURAL LEFT JOIN b USING (id, [region id])");
+        assert!(join.natural);
+        assert_eq!(
+            join.constraint,
+            Some(JoinConstraint::Using(vec![
+                Identifier::new("id"),
+                Identifier::quoted("region id", QuoteStyle::Bracket),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_joins_nest_left_to_right() {
+        let input = "SELECT * FROM a JOIN b ON x = y LEFT JOIN c ON y = z";
+        let join = parse_join(input);
+
+        assert_eq!(join.kind, JoinKind::Left { outer: false });
+        assert_eq!(join.span.start, 14);
+        assert_eq!(join.span.end, input.len());
+        match &join.left {
+            TableExpression::Join(inner) => assert_eq!(inner.kind, JoinKind::Join),
+            other => panic!("expected a join, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parenthesized_joins() {
+        let input = "SELECT * FROM t JOIN (u JOIN v ON u.id = v.id) ON t.id = u.id";
+        let join = parse_join(input);
+
+        assert!(matches!(join.left, TableExpression::Table(_)));
+        match &join.right {
+            TableExpression::Nested { span, expr } => {
+                assert_eq!(&input[span.start..span.end], "(u JOIN v ON u.id = v.id)");
+                assert!(matches!(**expr, TableExpression::Join(_)));
+            }
+            other => panic!("expected a nested join, got {:?}", other),
+        }
+        assert!(matches!(join.constraint, Some(JoinConstraint::On(_))));
+
+        let mut parser =
+            Parser::new("SELECT * FROM ((t JOIN u ON t.id = u.id)) LEFT JOIN w USING (id)");
+        let result = parser.parse_select().unwrap();
+        assert_eq!(first_table(&result).name, object_name("t"));
+
+        // a parenthesized query is still a derived table
+        let mut parser = Parser::new("SELECT * FROM ((SELECT 1) UNION (SELECT 2)) AS x");
+        let result = parser.parse_select().unwrap();
+        assert!(matches!(
+            result.from.unwrap()[0],
+            TableExpression::Derived { .. }
+        ));
+
+        let mut parser = Parser::new("SELECT * FROM (t JOIN u ON t.id = u.id");
+        assert!(parser.parse_select().is_err());
+    }
+
+    #[test]
+    fn test_comma_separated_from() {
+        let mut parser = Parser::new("SELECT * FROM a, b AS x JOIN c USING (id), d");
+        let result = parser.parse_select().unwrap();
+
+        let from = result.from.unwrap();
+        assert_eq!(from.len(), 3);
+        assert!(matches!(from[0], TableExpression::Table(_)));
+        assert!(matches!(from[1], TableExpression::Join(_)));
+        assert!(matches!(from[2], TableExpression::Table(_)));
+    }
+
+    #[test]
+    fn test_invalid_joins() {
+        for input in [
+            "SELECT * FROM a LEFT b",
+            "SELECT * FROM a NATURAL b",
+            "SELECT * FROM a JOIN",
+            "SELECT * FROM a JOIN b USING id",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_select().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_having_and_order_by() {
+        let input = "SELECT a FROM t GROUP BY a HAVING a > 1 ORDER BY a DESC NULLS LAST, b, c ASC";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_query().unwrap();
+
+        assert!(matches!(
+            select(&result).having,
+            Some(Expression::BinaryOperation {
+                op: Operator::GreaterThan,
+                ..
+            })
+        ));
+
+        let order_by = result.order_by.unwrap();
+        assert_eq!(order_by.len(), 3);
+        assert_eq!(order_by[0].direction, Some(SortDirection::Desc));
+        assert_eq!(order_by[0].nulls, Some(NullsOrder::Last));
+        assert_eq!(
+            &input[order_by[0].span.start..order_by[0].span.end],
+            "a DESC NULLS LAST"
+        );
+        assert_eq!(order_by[1].direction, None);
+        assert_eq!(order_by[1].nulls, None);
+        assert_eq!(order_by[2].direction, Some(SortDirection::Asc));
+    }
+
+    #[test]
+    fn test_limit_and_offset() {
+        for input in [
+            "SELECT a FROM t LIMIT 10 OFFSET 5",
+            "SELECT a FROM t OFFSET 5 LIMIT 10",
+        ] {
+            let mut parser = Parser::new(input);
+            let result = parser.parse_query().unwrap();
+
+            assert!(matches!(result.limit, Some(Expression::Literal { .. })));
+            let offset = result.offset.unwrap();
+            assert_eq!(offset.rows, None);
+            assert_eq!(&input[offset.span.start..offset.span.end], "OFFSET 5");
+        }
+    }
+
+    #[test]
+    fn test_offset_fetch() {
+        let input = "SELECT a FROM t ORDER BY a OFFSET 10 ROWS FETCH NEXT 5 ROWS ONLY";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_query().unwrap();
+
+        assert_eq!(result.offset.unwrap().rows, Some(RowsKeyword::Rows));
+        let fetch = result.fetch.unwrap();
+        assert!(fetch.next);
+        assert!(fetch.quantity.is_some());
+        assert_eq!(fetch.rows, RowsKeyword::Rows);
+        assert_eq!(
+            &input[fetch.span.start..fetch.span.end],
+            "FETCH NEXT 5 ROWS ONLY"
+        );
+
+        let mut parser = Parser::new("SELECT a FROM t FETCH FIRST ROW ONLY");
+        let fetch = parser.parse_query().unwrap().fetch.unwrap();
+        assert!(!fetch.next);
+        assert!(fetch.quantity.is_none());
+        assert_eq!(fetch.rows, RowsKeyword::Row);
+    }
+
+    #[test]
+    fn test_invalid_trailing_clauses() {
+        for input in [
+            "SELECT a FROM t ORDER a",
+            "SELECT a FROM t ORDER BY a NULLS",
+            "SELECT a FROM t LIMIT",
+            "SELECT a FROM t FETCH 5 ROWS ONLY",
+            "SELECT a FROM t FETCH FIRST 5 ONLY",
+            "SELECT a FROM t FETCH FIRST 5 ROWS",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_query().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_trailing_clause_words_as_names() {
+        // none of these are reserved, they're only keywords where a clause expects them
+        let input = "SELECT first, last, rows AS limit, next AS offset, row only \
+                     FROM names AS fetch ORDER BY desc ASC NULLS FIRST, nulls LIMIT 5";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_query().unwrap();
+
+        let columns: Vec<_> = select(&result)
+            .columns
+            .iter()
+            .map(|item| match item {
+                SelectItem::Expression {
+                    expr: Expression::Column { name, .. },
+                    alias,
+                    ..
+                } => (name.to_string(), alias.as_ref().map(|a| a.value.clone())),
+                other => panic!("expected a column, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            columns,
+            [
+                ("first".to_string(), None),
+                ("last".to_string(), None),
+                ("rows".to_string(), Some("limit".to_string())),
+                ("next".to_string(), Some("offset".to_string())),
+                ("row".to_string(), Some("only".to_string())),
+            ]
+        );
+        assert_eq!(
+            first_table(select(&result)).alias,
+            Some(Identifier::new("fetch"))
+        );
+
+        let order_by = result.order_by.unwrap();
+        assert_eq!(order_by[0].direction, Some(SortDirection::Asc));
+        assert_eq!(order_by[0].nulls, Some(NullsOrder::First));
+        assert!(
+            matches!(&order_by[1].expr, Expression::Column { name, .. } if *name == object_name("nulls"))
+        );
+        assert!(result.limit.is_some());
+
+        // without AS, a word starting the next clause is never an alias
+        let mut parser = Parser::new("SELECT a FROM t limit 5");
+        let result = parser.parse_query().unwrap();
+        assert_eq!(first_table(select(&result)).alias, None);
+        assert!(result.limit.is_some());
+    }
+
+    #[test]
+    fn test_scalar_subquery_in_select_list() {
+        let input = "SELECT id, (SELECT total FROM orders) AS top FROM users";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_select().unwrap();
+
+        match &result.columns[1] {
+            SelectItem::Expression {
+                expr: Expression::Subquery { span, query },
+                alias,
+                ..
+            } => {
+                assert_eq!(&input[span.start..span.end], "(SELECT total FROM orders)");
+                assert_eq!(first_table(select(query)).name, object_name("orders"));
+                assert_eq!(alias, &Some(Identifier::new("top")));
+            }
+            other => panic!("expected a subquery, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_in_and_exists_subqueries() {
+        let condition = where_condition(
+            "SELECT a FROM t WHERE a NOT IN (SELECT b FROM u) AND EXISTS (SELECT c FROM v)",
+        );
+
+        match condition {
+            Expression::BinaryOperation {
+                left,
+                op: Operator::And,
+                right,
+                ..
+            } => {
+                assert!(matches!(
+                    *left,
+                    Expression::InSubquery { negated: true, .. }
+                ));
+                assert!(matches!(*right, Expression::Exists { .. }));
+            }
+            other => panic!("expected AND, got {:?}", other),
+        }
+
+        // IN binds tighter than AND but looser than arithmetic
+        assert_eq!(
+            grouping(&where_condition(
+                "SELECT a FROM t WHERE a + 1 IN (SELECT b FROM u) AND c = 1"
+            )),
+            "(((a + 1) IN subquery) AND (c = 1))"
+        );
+    }
+
+    #[test]
+    fn test_derived_tables() {
+        let input =
+            "SELECT * FROM (SELECT id FROM users) AS u JOIN (SELECT id FROM orders) o ON a = b";
+        let join = parse_join(input);
+
+        match (&join.left, &join.right) {
+            (
+                TableExpression::Derived {
+                    span,
+                    alias: Some(left),
+                    ..
+                },
+                TableExpression::Derived {
+                    alias: Some(right), ..
+                },
+            ) => {
+                assert_eq!(&input[span.start..span.end], "(SELECT id FROM users) AS u");
+                assert_eq!(left, &Identifier::new("u"));
+                assert_eq!(right, &Identifier::new("o"));
+            }
+            other => panic!("expected derived tables, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unclosed_subquery() {
+        for input in [
+            "SELECT (SELECT a FROM t FROM u",
+            "SELECT a FROM (SELECT a FROM t",
+            "SELECT a FROM t WHERE EXISTS SELECT a FROM t",
+            "SELECT a FROM t WHERE a IN (SELECT a FROM t",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_select().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_with_clause() {
+        let input = "WITH RECURSIVE tree (id, parent) AS (SELECT id, parent FROM nodes), \
+                     recent AS NOT MATERIALIZED (SELECT id FROM tree) \
+                     SELECT id FROM recent";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_query().unwrap();
+
+        assert_eq!(result.span.start, 0);
+        assert_eq!(first_table(select(&result)).name, object_name("recent"));
+
+        let with = result.with.unwrap();
+        assert!(with.recursive);
+        assert_eq!(with.ctes.len(), 2);
+
+        let tree = &with.ctes[0];
+        assert_eq!(tree.name, "tree");
+        assert_eq!(
+            tree.columns,
+            vec![Identifier::new("id"), Identifier::new("parent")]
+        );
+        assert_eq!(tree.materialized, None);
+        assert_eq!(first_table(select(&tree.query)).name, object_name("nodes"));
+
+        let recent = &with.ctes[1];
+        assert!(recent.columns.is_empty());
+        assert_eq!(recent.materialized, Some(Materialization::NotMaterialized));
+        assert_eq!(
+            &input[recent.span.start..recent.span.end],
+            "recent AS NOT MATERIALIZED (SELECT id FROM tree)"
+        );
+    }
+
+    #[test]
+    fn test_with_words_as_names() {
+        let input = "WITH recursive AS (SELECT materialized FROM t), \
+                     materialized (recursive) AS MATERIALIZED (SELECT 1) \
+                     SELECT recursive FROM materialized";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_query().unwrap();
+
+        let with = result.with.as_ref().unwrap();
+        assert!(!with.recursive);
+        assert_eq!(with.ctes[0].name, "recursive");
+        assert_eq!(with.ctes[0].materialized, None);
+        assert_eq!(with.ctes[1].name, "materialized");
+        assert_eq!(with.ctes[1].columns, vec![Identifier::new("recursive")]);
+        assert_eq!(
+            with.ctes[1].materialized,
+            Some(Materialization::Materialized)
+        );
+        assert_eq!(
+            first_table(select(&result)).name,
+            object_name("materialized")
+        );
+    }
+
+    #[test]
+    fn test_invalid_with_clause() {
+        for input in [
+            "WITH SELECT a FROM t",
+            "WITH x (SELECT a FROM t) SELECT a FROM x",
+            "WITH x AS SELECT a FROM t",
+            "WITH x AS (SELECT a FROM t)",
+            "WITH x AS (SELECT a FROM t), SELECT a FROM x",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_query().is_err(), "{}", input);
+        }
+    }
+
+    // the shape of a set expression, e.g. `((t UNION u) INTERSECT v)`
+    fn set_shape(expr: &SetExpression) -> String {
+        match expr {
+            SetExpression::Select(select) => first_table(select).name.to_string(),
+            SetExpression::Query(query) => format!("[{}]", set_shape(&query.body)),
+            SetExpression::Operation {
+                left,
+                op,
+                quantifier,
+                right,
+                ..
+            } => match quantifier {
+                Some(quantifier) => format!(
+                    "({} {} {} {})",
+                    set_shape(left),
+                    op,
+                    quantifier,
+                    set_shape(right)
+                ),
+                None => format!("({} {} {})", set_shape(left), op, set_shape(right)),
+            },
+        }
+    }
+
+    fn parse_set_shape(input: &str) -> String {
+        set_shape(&parse_statement!(Query, input).body)
+    }
+
+    #[test]
+    fn test_set_operation_precedence() {
+        assert_eq!(
+            parse_set_shape("SELECT a FROM t UNION SELECT a FROM u INTERSECT SELECT a FROM v"),
+            "(t UNION (u INTERSECT v))"
+        );
+        assert_eq!(
+            parse_set_shape("SELECT a FROM t EXCEPT SELECT a FROM u UNION ALL SELECT a FROM v"),
+            "((t EXCEPT u) UNION ALL v)"
+        );
+        assert_eq!(
+            parse_set_shape(
+                "SELECT a FROM t INTERSECT DISTINCT (SELECT a FROM u UNION SELECT a FROM v)"
+            ),
+            "(t INTERSECT DISTINCT [(u UNION v)])"
+        );
+    }
+
+    #[test]
+    fn test_trailing_clauses_apply_to_whole_union() {
+        let input = "(SELECT a FROM t ORDER BY a LIMIT 1) UNION SELECT a FROM u ORDER BY a LIMIT 5";
+        let mut parser = Parser::new(input);
+        let result = parser.parse_query().unwrap();
+
+        assert_eq!(result.span.end, input.len());
+        assert!(result.order_by.is_some());
+        assert!(result.limit.is_some());
+
+        match &result.body {
+            SetExpression::Operation { left, right, .. } => {
+                match left.as_ref() {
+                    SetExpression::Query(query) => {
+                        assert!(query.order_by.is_some());
+                        assert!(query.limit.is_some());
+                    }
+                    other => panic!("expected a parenthesized query, got {:?}", other),
+                }
+                assert!(matches!(right.as_ref(), SetExpression::Select(_)));
+            }
+            other => panic!("expected a set operation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_union_in_subquery() {
+        let condition =
+            where_condition("SELECT a FROM t WHERE a IN (SELECT b FROM u UNION SELECT c FROM v)");
+
+        match condition {
+            Expression::InSubquery { query, .. } => {
+                assert_eq!(set_shape(&query.body), "(u UNION v)")
+            }
+            other => panic!("expected IN, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_set_operations() {
+        for input in [
+            "SELECT a FROM t UNION",
+            "SELECT a FROM t UNION ALL ORDER BY a",
+            "SELECT a FROM t UNION (SELECT a FROM u",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_query().is_err(), "{}", input);
+        }
+    }
+
+    fn select_expression(input: &str) -> Expression {
+        let query = parse_statement!(Query, input);
+        match select(&query).columns.clone().into_iter().next() {
+            Some(SelectItem::Expression { expr, .. }) => expr,
+            other => panic!("expected an expression, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_function_calls() {
+        match select_expression("SELECT dbo.fn_x(a, 1 + 2) FROM t") {
+            Expression::Function {
+                name,
+                args,
+                quantifier,
+                ..
+            } => {
+                assert_eq!(
+                    name,
+                    ObjectName(vec![Identifier::new("dbo"), Identifier::new("fn_x")])
+                );
+                assert_eq!(args.len(), 2);
+                assert_eq!(quantifier, None);
+            }
+            other => panic!("expected a function, got {:?}", other),
+        }
+
+        match select_expression("SELECT now() FROM t") {
+            Expression::Function { args, .. } => assert!(args.is_empty()),
+            other => panic!("expected a function, got {:?}", other),
+        }
+
+        match select_expression("SELECT LEFT(name, 3) FROM t") {
+            Expression::Function { name, .. } => assert_eq!(name.to_string(), "LEFT"),
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_calls() {
+        match select_expression("SELECT COUNT(*) FROM t") {
+            Expression::Function { args, .. } => {
+                assert!(matches!(args[..], [Expression::Asterisk { .. }]))
+            }
+            other => panic!("expected a function, got {:?}", other),
+        }
+
+        match select_expression("SELECT COUNT(DISTINCT o.customer_id) FROM t") {
+            Expression::Function {
+                args, quantifier, ..
+            } => {
+                assert_eq!(quantifier, Some(Quantifier::Distinct));
+                assert!(matches!(
+                    &args[..],
+                    [Expression::Column { name, .. }] if *name == object_name("o.customer_id")
+                ));
+            }
+            other => panic!("expected a function, got {:?}", other),
+        }
+
+        let input = "SELECT percentile_cont(0.5) WITHIN GROUP (ORDER BY price DESC) FILTER (WHERE price > 0) FROM t";
+        match select_expression(input) {
+            Expression::Function {
+                span,
+                within_group,
+                filter,
+                ..
+            } => {
+                assert_eq!(within_group.len(), 1);
+                assert_eq!(within_group[0].direction, Some(SortDirection::Desc));
+                assert!(filter.is_some());
+                assert_eq!(span.end, input.len() - " FROM t".len());
+            }
+            other => panic!("expected a function, got {:?}", other),
+        }
+
+        match select_expression("SELECT string_agg(name, ',' ORDER BY name) FROM t") {
+            Expression::Function { args, order_by, .. } => {
+                assert_eq!(args.len(), 2);
+                assert_eq!(order_by.len(), 1);
+            }
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_modifier_words_as_names() {
+        let mut parser = Parser::new("SELECT filter, within FROM rules");
+        assert_eq!(parser.parse_select().unwrap().columns.len(), 2);
+
+        // only a paren or GROUP after them makes them part of the call
+        let mut parser = Parser::new("SELECT count(*) filter, max(a) within FROM rules");
+        match &parser.parse_select().unwrap().columns[..] {
+            [SelectItem::Expression {
+                expr: Expression::Function { filter: None, .. },
+                alias: Some(first),
+                ..
+            }, SelectItem::Expression {
+                expr: Expression::Function { within_group, .. },
+                alias: Some(second),
+                ..
+            }] => {
+                assert_eq!(*first, Identifier::new("filter"));
+                assert!(within_group.is_empty());
+                assert_eq!(*second, Identifier::new("within"));
+            }
+            other => panic!("expected two aliased calls, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_function_calls() {
+        for input in [
+            "SELECT count(a FROM t",
+            "SELECT count(a,) FROM t",
+            "SELECT rank() WITHIN GROUP ORDER BY a FROM t",
+            "SELECT count(*) FILTER (a > 1) FROM t",
+            "SELECT a. FROM t",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_select().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_window_functions() {
+        let input = "SELECT sum(amount) OVER (PARTITION BY dept, region ORDER BY day ROWS BETWEEN 2 PRECEDING AND CURRENT ROW) FROM t";
+        match select_expression(input) {
+            Expression::Function {
+                span,
+                over: Some(Window::Specification(spec)),
+                ..
+            } => {
+                assert_eq!(spec.base, None);
+                assert_eq!(spec.partition_by.len(), 2);
+                assert_eq!(spec.order_by.len(), 1);
+                let frame = spec.frame.unwrap();
+                assert_eq!(frame.units, FrameUnits::Rows);
+                assert!(matches!(frame.start, FrameBound::Preceding(_)));
+                assert_eq!(frame.end, Some(FrameBound::CurrentRow));
+                assert_eq!(span.end, input.len() - " FROM t".len());
+            }
+            other => panic!("expected a window function, got {:?}", other),
+        }
+
+        match select_expression("SELECT rank() OVER w FROM t") {
+            Expression::Function {
+                over: Some(Window::Named(name)),
+                ..
+            } => assert_eq!(name, "w"),
+            other => panic!("expected a named window, got {:?}", other),
+        }
+
+        match select_expression("SELECT count(*) OVER (w RANGE UNBOUNDED PRECEDING) FROM t") {
+            Expression::Function {
+                over: Some(Window::Specification(spec)),
+                ..
+            } => {
+                assert!(matches!(spec.base, Some(ref base) if base == "w"));
+                let frame = spec.frame.unwrap();
+                assert_eq!(frame.units, FrameUnits::Range);
+                assert_eq!(frame.start, FrameBound::UnboundedPreceding);
+                assert_eq!(frame.end, None);
+            }
+            other => panic!("expected a window function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_window_clause() {
+        let mut parser = Parser::new(
+            "SELECT rank() OVER w FROM t HAVING count(*) > 1 WINDOW w AS (ORDER BY a), v AS (w GROUPS 1 FOLLOWING)",
+        );
+        let stmt = parser.parse_select().unwrap();
+        let windows = stmt.window.unwrap();
+
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].name, "w");
+        assert_eq!(windows[0].spec.order_by.len(), 1);
+        assert_eq!(windows[1].name, "v");
+        assert_eq!(
+            windows[1].spec.frame.as_ref().map(|frame| &frame.units),
+            Some(&FrameUnits::Groups)
+        );
+    }
+
+    #[test]
+    fn test_invalid_windows() {
+        for input in [
+            "SELECT rank() OVER (w FROM t",
+            "SELECT rank() OVER (PARTITION a) FROM t",
+            "SELECT sum(a) OVER (ROWS BETWEEN 1 PRECEDING) FROM t",
+            "SELECT sum(a) OVER (ROWS CURRENT) FROM t",
+            "SELECT sum(a) OVER (ORDER BY a FROM t",
+            "SELECT rank() OVER w FROM t WINDOW w (ORDER BY a)",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_select().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_window_words_as_names() {
+        let input = "SELECT range, current, groups AS partition, rank() over \
+                     FROM t AS window WHERE preceding < following AND unbounded";
+        let mut parser = Parser::new(input);
+        let stmt = parser.parse_select().unwrap();
+
+        assert_eq!(stmt.columns.len(), 4);
+        assert!(matches!(
+            &stmt.columns[3],
+            SelectItem::Expression {
+                expr: Expression::Function { over: None, .. },
+                alias: Some(alias),
+                ..
+            } if *alias == "over"
+        ));
+        assert_eq!(first_table(&stmt).alias, Some(Identifier::new("window")));
+        assert!(stmt.where_clause.is_some());
+        assert!(stmt.window.is_none());
+
+        // a window can still be based on one named like a frame keyword
+        match select_expression("SELECT sum(a) OVER (current ROWS UNBOUNDED PRECEDING) FROM t") {
+            Expression::Function {
+                over: Some(Window::Specification(spec)),
+                ..
+            } => {
+                assert!(matches!(spec.base, Some(ref base) if base == "current"));
+                assert_eq!(spec.frame.unwrap().units, FrameUnits::Rows);
+            }
+            other => panic!("expected a window function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_case_expressions() {
+        let input =
+            "SELECT CASE WHEN a > 1 THEN 'big' WHEN a > 0 THEN 'small' ELSE 'none' END FROM t";
+        match select_expression(input) {
+            Expression::Case {
+                span,
+                operand,
+                conditions,
+                else_result,
+            } => {
+                assert!(operand.is_none());
+                assert_eq!(conditions.len(), 2);
+                assert!(matches!(
+                    conditions[0].condition,
+                    Expression::BinaryOperation {
+                        op: Operator::GreaterThan,
+                        ..
+                    }
+                ));
+                assert!(else_result.is_some());
+                assert_eq!(span.end, input.len() - " FROM t".len());
+            }
+            other => panic!("expected a CASE, got {:?}", other),
+        }
+
+        match select_expression("SELECT CASE status WHEN 1 THEN 'on' END FROM t") {
+            Expression::Case {
+                operand,
+                conditions,
+                else_result,
+                ..
+            } => {
+                assert!(matches!(
+                    operand.as_deref(),
+                    Some(Expression::Column { .. })
+                ));
+                assert_eq!(conditions.len(), 1);
+                assert!(else_result.is_none());
+            }
+            other => panic!("expected a CASE, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_case_expressions() {
+        for input in [
+            "SELECT CASE END FROM t",
+            "SELECT CASE ELSE 1 END FROM t",
+            "SELECT CASE WHEN a 1 END FROM t",
+            "SELECT CASE WHEN a THEN 1 FROM t",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_select().is_err(), "{}", input);
+        }
+    }
+
+    fn cast_type(input: &str, dialect: SqlDialect) -> (CastKind, DataType) {
+        let query = parse_statement!(Query, input, dialect);
+        match &select(&query).columns[0] {
+            SelectItem::Expression {
+                expr: Expression::Cast {
+                    kind, data_type, ..
+                },
+                ..
+            } => (*kind, data_type.clone()),
+            other => panic!("expected a cast, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_casts() {
+        let (kind, data_type) = cast_type(
+            "SELECT CAST(a AS DECIMAL(10, 2)) FROM t",
+            SqlDialect::Generic,
+        );
+        assert_eq!(kind, CastKind::Cast);
+        assert_eq!(data_type.name, ObjectName(vec![Identifier::new("DECIMAL")]));
+        assert_eq!(
+            data_type.arguments,
+            vec![
+                TypeArgument::Number("10".to_string()),
+                TypeArgument::Number("2".to_string())
+            ]
+        );
+
+        let (kind, data_type) = cast_type(
+            "SELECT TRY_CAST(a AS nvarchar(max)) FROM t",
+            SqlDialect::TSql,
+        );
+        assert_eq!(kind, CastKind::TryCast);
+        assert_eq!(data_type.arguments, vec![TypeArgument::Max]);
+
+        let (kind, data_type) = cast_type("SELECT a::double precision FROM t", SqlDialect::Generic);
+        assert_eq!(kind, CastKind::DoubleColon);
+        assert_eq!(data_type.name.to_string(), "double precision");
+
+        let (_, data_type) = cast_type(
+            "SELECT a::timestamp(3) WITHOUT TIME ZONE FROM t",
+            SqlDialect::PostgreSql,
+        );
+        assert_eq!(data_type.time_zone, Some(TimeZone::Without));
+
+        let (_, data_type) = cast_type("SELECT a::app.money FROM t", SqlDialect::Generic);
+        assert_eq!(data_type.name.0.len(), 2);
+    }
+
+    #[test]
+    fn test_array_types() {
+        let (_, data_type) = cast_type("SELECT a::int[][3] FROM t", SqlDialect::PostgreSql);
+        assert_eq!(
+            data_type.array_dimensions,
+            vec![None, Some("3".to_string())]
+        );
+        assert_eq!(data_type.span.end, "SELECT a::int[][3]".len());
+    }
+
+    #[test]
+    fn test_array_types_in_generic() {
+        // a bracket straight after a type is an array bound, not a quoted alias
+        let mut parser = Parser::new("SELECT x::int[] FROM t");
+        match &parser.parse_select().unwrap().columns[..] {
+            [SelectItem::Expression {
+                expr: Expression::Cast { data_type, .. },
+                alias: None,
+                ..
+            }] => assert_eq!(data_type.array_dimensions, vec![None]),
+            other => panic!("expected an unaliased cast, got {:?}", other),
+        }
+
+        let (kind, data_type) = cast_type("SELECT CAST(x AS INT[3]) FROM t", SqlDialect::Generic);
+        assert_eq!(kind, CastKind::Cast);
+        assert_eq!(data_type.array_dimensions, vec![Some("3".to_string())]);
+
+        let (_, data_type) = cast_type("SELECT x::varchar(10)[] FROM t", SqlDialect::Generic);
+        assert_eq!(data_type.arguments.len(), 1);
+        assert_eq!(data_type.array_dimensions, vec![None]);
+
+        // an empty bracket identifier is never an alias
+        let mut parser = Parser::new("SELECT x::int [] FROM t");
+        assert!(matches!(
+            parser.parse_statements(),
+            Err(ParseError::Lexical {
+                error: LexError::EmptyIdentifier,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_subscripts() {
+        for dialect in [SqlDialect::Generic, SqlDialect::PostgreSql] {
+            let input = "SELECT tags[1], grid[i][j + 1] AS cell, [tags] FROM t";
+            let mut parser = Parser::with_dialect(input, dialect);
+            let result = parser.parse_select();
+            if dialect == SqlDialect::PostgreSql {
+                // postgres has no bracket quoting, so `[tags]` is no column
+                assert!(result.is_err());
+                continue;
+            }
+            let stmt = result.unwrap();
+
+            match &stmt.columns[0] {
+                SelectItem::Expression {
+                    expr: Expression::Subscript { span, expr, index },
+                    ..
+                } => {
+                    assert!(
+                        matches!(**expr, Expression::Column { ref name, .. } if *name == object_name("tags"))
+                    );
+                    assert!(matches!(**index, Expression::Literal { .. }));
+                    assert_eq!(&input[span.start..span.end], "tags[1]");
+                }
+                other => panic!("expected a subscript, got {:?}", other),
+            }
+            match &stmt.columns[1] {
+                SelectItem::Expression {
+                    expr: Expression::Subscript { expr, index, .. },
+                    alias: Some(alias),
+                    ..
+                } => {
+                    assert!(matches!(**expr, Expression::Subscript { .. }));
+                    assert!(matches!(**index, Expression::BinaryOperation { .. }));
+                    assert_eq!(*alias, "cell");
+                }
+                other => panic!("expected a nested subscript, got {:?}", other),
+            }
+            assert!(matches!(
+                &stmt.columns[2],
+                SelectItem::Expression { expr: Expression::Column { name, .. }, .. }
+                    if name.0[0].quote_style == QuoteStyle::Bracket
+            ));
+        }
+
+        let mut parser = Parser::with_dialect("SELECT arr[1] FROM t", SqlDialect::PostgreSql);
+        assert!(matches!(
+            &parser.parse_select().unwrap().columns[0],
+            SelectItem::Expression {
+                expr: Expression::Subscript { .. },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_convert() {
+        let input = "SELECT CONVERT(varchar(10), created_at, 120) FROM t";
+        match select_expression(input) {
+            Expression::Cast {
+                span,
+                kind,
+                expr,
+                data_type,
+                style,
+            } => {
+                assert_eq!(kind, CastKind::Convert);
+                assert!(matches!(*expr, Expression::Column { .. }));
+                assert_eq!(data_type.arguments.len(), 1);
+                assert!(style.is_some());
+                assert_eq!(span.end, input.len() - " FROM t".len());
+            }
+            other => panic!("expected a convert, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cast_precedence() {
+        // `::` binds tighter than unary minus and multiplication
+        match select_expression("SELECT -a::int * 2 FROM t") {
+            Expression::BinaryOperation { left, .. } => match *left {
+                Expression::UnaryOperation { expr, .. } => {
+                    assert!(matches!(*expr, Expression::Cast { .. }))
+                }
+                other => panic!("expected a unary operation, got {:?}", other),
+            },
+            other => panic!("expected a binary operation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_casts() {
+        for input in [
+            "SELECT CAST(a) FROM t",
+            "SELECT CAST(a AS) FROM t",
+            "SELECT CAST(a AS int(x)) FROM t",
+            "SELECT CONVERT(int) FROM t",
+            "SELECT a:: FROM t",
+            "SELECT a::timestamp WITHOUT ZONE FROM t",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_select().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_predicate_precedence() {
+        let expr = where_condition("SELECT a FROM t WHERE a BETWEEN 1 AND b + 2 AND c = 3");
+        assert_eq!(grouping(&expr), "((a BETWEEN 1 (b + 2)) AND (c = 3))");
+
+        let expr = where_condition("SELECT a FROM t WHERE NOT a IN (1, 2) OR b IS NULL");
+        assert_eq!(grouping(&expr), "((NOT (a IN 1 2)) OR (b IS NULL))");
+
+        let expr = where_condition("SELECT a FROM t WHERE a + 1 LIKE b AND c");
+        assert_eq!(grouping(&expr), "(((a + 1) LIKE b) AND c)");
+    }
+
+    #[test]
+    fn test_predicates() {
+        match where_condition("SELECT a FROM t WHERE a NOT IN (1, 2, 3)") {
+            Expression::InList { list, negated, .. } => {
+                assert_eq!(list.len(), 3);
+                assert!(negated);
+            }
+            other => panic!("expected an IN list, got {:?}", other),
+        }
+
+        match where_condition("SELECT a FROM t WHERE a NOT BETWEEN 1 AND 2") {
+            Expression::Between { negated, .. } => assert!(negated),
+            other => panic!("expected BETWEEN, got {:?}", other),
+        }
+
+        let input = "SELECT a FROM t WHERE name NOT ILIKE 'a!%%' ESCAPE '!'";
+        match where_condition(input) {
+            Expression::Like {
+                span,
+                kind,
+                escape,
+                negated,
+                ..
+            } => {
+                assert_eq!(kind, LikeKind::ILike);
+                assert!(escape.is_some());
+                assert!(negated);
+                assert_eq!(span.end, input.len());
+            }
+            other => panic!("expected ILIKE, got {:?}", other),
+        }
+
+        match where_condition("SELECT a FROM t WHERE name SIMILAR TO '%(b|d)%'") {
+            Expression::Like { kind, negated, .. } => {
+                assert_eq!(kind, LikeKind::SimilarTo);
+                assert!(!negated);
+            }
+            other => panic!("expected SIMILAR TO, got {:?}", other),
+        }
+
+        match where_condition("SELECT a FROM t WHERE done IS NOT TRUE") {
+            Expression::Is { value, negated, .. } => {
+                assert_eq!(value, IsValue::True);
+                assert!(negated);
+            }
+            other => panic!("expected IS, got {:?}", other),
+        }
+
+        match where_condition("SELECT a FROM t WHERE a IS DISTINCT FROM b") {
+            Expression::IsDistinctFrom { negated, .. } => assert!(!negated),
+            other => panic!("expected IS DISTINCT FROM, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_predicates() {
+        for input in [
+            "SELECT a FROM t WHERE a IN ()",
+            "SELECT a FROM t WHERE a IN (1, 2",
+            "SELECT a FROM t WHERE a BETWEEN 1",
+            "SELECT a FROM t WHERE a NOT SIMILAR 'x'",
+            "SELECT a FROM t WHERE a LIKE 'x' ESCAPE",
+            "SELECT a FROM t WHERE a IS 1",
+            "SELECT a FROM t WHERE a IS DISTINCT b",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_select().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_predicate_words_as_names() {
+        let mut parser = Parser::new("SELECT escape, to, similar FROM t WHERE to LIKE escape");
+        let stmt = parser.parse_select().unwrap();
+        assert_eq!(stmt.columns.len(), 3);
+        assert!(matches!(
+            stmt.where_clause.unwrap().condition,
+            Expression::Like { escape: None, .. }
+        ));
+
+        // only TO after it makes SIMILAR a predicate
+        let mut parser = Parser::new("SELECT a similar, b FROM t");
+        match &parser.parse_select().unwrap().columns[0] {
+            SelectItem::Expression {
+                alias: Some(alias), ..
+            } => assert_eq!(*alias, "similar"),
+            other => panic!("expected an alias, got {:?}", other),
+        }
+
+        match where_condition("SELECT a FROM t WHERE a NOT SIMILAR TO to ESCAPE escape") {
+            Expression::Like {
+                kind,
+                pattern,
+                escape: Some(escape),
+                negated,
+                ..
+            } => {
+                assert_eq!(kind, LikeKind::SimilarTo);
+                assert!(negated);
+                assert!(
+                    matches!(*pattern, Expression::Column { ref name, .. } if *name == object_name("to"))
+                );
+                assert!(
+                    matches!(*escape, Expression::Column { ref name, .. } if *name == object_name("escape"))
+                );
+            }
+            other => panic!("expected SIMILAR TO, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_multi_part_names() {
+        let mut parser = Parser::new(
+            "SELECT srv.db.dbo.orders.id, d.sales.* FROM srv.db.dbo.orders, proj.dataset.sales AS d WHERE d.id = 1",
+        );
+        let stmt = parser.parse_select().unwrap();
+
+        match &stmt.columns[0] {
+            SelectItem::Expression {
+                expr: Expression::Column { name, .. },
+                ..
+            } => assert_eq!(name.0.len(), 5),
+            other => panic!("expected a column, got {:?}", other),
+        }
+        match &stmt.columns[1] {
+            SelectItem::QualifiedWildcard { qualifier, .. } => {
+                assert_eq!(*qualifier, object_name("d.sales"))
+            }
+            other => panic!("expected a qualified wildcard, got {:?}", other),
+        }
+
+        assert_eq!(first_table(&stmt).name, object_name("srv.db.dbo.orders"));
+        match &stmt.from.as_ref().unwrap()[1] {
+            TableExpression::Table(table) => {
+                assert_eq!(table.name, object_name("proj.dataset.sales"));
+                assert_eq!(table.span.end, "SELECT srv.db.dbo.orders.id, d.sales.* FROM srv.db.dbo.orders, proj.dataset.sales AS d".len());
+            }
+            other => panic!("expected a table, got {:?}", other),
+        }
+
+        match stmt.where_clause.unwrap().condition {
+            Expression::BinaryOperation { left, .. } => {
+                assert!(
+                    matches!(*left, Expression::Column { ref name, .. } if *name == object_name("d.id"))
+                )
+            }
+            other => panic!("expected a comparison, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_qualified_names() {
+        for input in [
+            "SELECT a. FROM t",
+            "SELECT a.b. FROM t",
+            "SELECT a FROM db.",
+            "SELECT a FROM db.dbo.",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_select().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_select_quantifiers() {
+        let mut parser = Parser::new("SELECT DISTINCT city FROM t");
+        let stmt = parser.parse_select().unwrap();
+        assert_eq!(stmt.quantifier, Some(SelectQuantifier::Distinct));
+
+        let mut parser = Parser::new("SELECT ALL city FROM t");
+        let stmt = parser.parse_select().unwrap();
+        assert_eq!(stmt.quantifier, Some(SelectQuantifier::All));
+
+        let mut parser = Parser::new("SELECT DISTINCT ON (a, b) a, b, c FROM t");
+        let stmt = parser.parse_select().unwrap();
+        match stmt.quantifier {
+            Some(SelectQuantifier::DistinctOn(expressions)) => assert_eq!(expressions.len(), 2),
+            other => panic!("expected DISTINCT ON, got {:?}", other),
+        }
+        assert_eq!(stmt.columns.len(), 3);
+
+        let mut parser = Parser::new("SELECT city FROM t");
+        assert_eq!(parser.parse_select().unwrap().quantifier, None);
+    }
+
+    #[test]
+    fn test_select_without_from() {
+        let input = "SELECT 1, GETDATE() AS now";
+        let mut parser = Parser::new(input);
+        let stmt = parser.parse_select().unwrap();
+
+        assert_eq!(stmt.from, None);
+        assert_eq!(stmt.columns.len(), 2);
+        assert_eq!(stmt.span.end, input.len());
+
+        let mut parser = Parser::new("SELECT 1 WHERE 1 = 1");
+        let stmt = parser.parse_select().unwrap();
+        assert_eq!(stmt.from, None);
+        assert!(stmt.where_clause.is_some());
+    }
+
+    #[test]
+    fn test_invalid_select_quantifiers() {
+        for input in [
+            "SELECT DISTINCT FROM t",
+            "SELECT DISTINCT ON a FROM t",
+            "SELECT DISTINCT ON () a FROM t",
+            "SELECT DISTINCT ON (a a FROM t",
+            "SELECT ALL DISTINCT a FROM t",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_select().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_insert_values() {
+        let input =
+            "INSERT INTO dbo.users (id, name) VALUES (1, 'a'), (2, 'b'), (3, DEFAULT_NAME())";
+        let insert = parse_statement!(Insert, input);
+
+        assert_eq!(insert.table, object_name("dbo.users"));
+        assert_eq!(
+            insert.columns,
+            vec![Identifier::new("id"), Identifier::new("name")]
+        );
+        match insert.source {
+            InsertSource::Values(rows) => {
+                assert_eq!(rows.len(), 3);
+                assert!(rows.iter().all(|row| row.len() == 2));
+            }
+            other => panic!("expected VALUES, got {:?}", other),
+        }
+        assert_eq!(insert.span.end, input.len());
+    }
+
+    #[test]
+    fn test_insert_sources() {
+        let insert = parse_statement!(Insert, "INSERT t DEFAULT VALUES");
+        assert_eq!(insert.table, object_name("t"));
+        assert_eq!(insert.source, InsertSource::DefaultValues);
+
+        // comments ahead of an INSERT ... SELECT stay with the insert
+        let insert = parse_statement!(Insert, "-- lead\nINSERT INTO t (SELECT a FROM s) -- tail");
+        assert!(matches!(insert.source, InsertSource::Query(_)));
+        assert!(insert.columns.is_empty());
+        assert_eq!(insert.comments[0].text, "-- lead");
+    }
+
+    #[test]
+    fn test_upserts() {
+        let insert = parse_statement!(
+            Insert,
+            "INSERT INTO t (id, n) VALUES (1, 2) ON CONFLICT (id) DO UPDATE SET n = excluded.n, m = 1 WHERE t.n < 5",
+        );
+        match insert.upsert {
+            Some(Upsert::OnConflict {
+                target: Some(ConflictTarget::Columns(columns)),
+                action:
+                    ConflictAction::DoUpdate {
+                        assignments,
+                        where_clause,
+                    },
+                ..
+            }) => {
+                assert_eq!(columns.len(), 1);
+                assert_eq!(assignments.len(), 2);
+                assert_eq!(assignments[0].column, object_name("n"));
+                assert!(where_clause.is_some());
+            }
+            other => panic!("expected ON CONFLICT DO UPDATE, got {:?}", other),
+        }
+
+        let insert = parse_statement!(
+            Insert,
+            "INSERT INTO t VALUES (1) ON CONFLICT ON CONSTRAINT t_pkey DO NOTHING"
+        );
+        assert!(matches!(
+            insert.upsert,
+            Some(Upsert::OnConflict {
+                target: Some(ConflictTarget::Constraint(_)),
+                action: ConflictAction::DoNothing,
+                ..
+            })
+        ));
+
+        let insert = parse_statement!(
+            Insert,
+            "INSERT INTO t (a) VALUES (1) ON DUPLICATE KEY UPDATE a = VALUES(a) + 1"
+        );
+        match insert.upsert {
+            Some(Upsert::OnDuplicateKeyUpdate { assignments, .. }) => {
+                assert!(matches!(
+                    assignments[0].value,
+                    Expression::BinaryOperation { .. }
+                ));
+            }
+            other => panic!("expected ON DUPLICATE KEY UPDATE, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_insert_returning_and_output() {
+        let insert = parse_statement!(
+            Insert,
+            "INSERT INTO t (a) VALUES (1) RETURNING id, a AS value"
+        );
+        let returning = insert.returning.unwrap();
+        assert_eq!(returning.kind, ReturningKind::Returning);
+        assert_eq!(returning.items.len(), 2);
+
+        let insert = parse_statement!(Insert, "INSERT INTO t (a) OUTPUT inserted.* VALUES (1)");
+        let output = insert.returning.unwrap();
+        assert_eq!(output.kind, ReturningKind::Output);
+        assert!(matches!(
+            output.items[..],
+            [SelectItem::QualifiedWildcard { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_insert_words_as_names() {
+        let insert = parse_statement!(
+            Insert,
+            "INSERT INTO t (values, returning, into) VALUES (1, 2, 3) RETURNING values, returning AS into",
+        );
+        assert_eq!(
+            insert.columns,
+            vec![
+                Identifier::new("values"),
+                Identifier::new("returning"),
+                Identifier::new("into")
+            ]
+        );
+        let returning = insert.returning.unwrap();
+        assert_eq!(returning.items.len(), 2);
+        assert!(matches!(
+            &returning.items[1],
+            SelectItem::Expression { alias: Some(alias), .. } if *alias == "into"
+        ));
+
+        // an OUTPUT item is never aliased by the VALUES after it
+        let insert = parse_statement!(
+            Insert,
+            "INSERT INTO t (values) OUTPUT inserted.values VALUES (1)"
+        );
+        assert!(matches!(
+            &insert.returning.unwrap().items[..],
+            [SelectItem::Expression { alias: None, .. }]
+        ));
+        assert!(matches!(insert.source, InsertSource::Values(_)));
+
+        let mut parser = Parser::new("SELECT values, returning, into FROM t");
+        assert_eq!(parser.parse_select().unwrap().columns.len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_inserts() {
+        for input in [
+            "INSERT INTO",
+            "INSERT INTO t (a, b",
+            "INSERT INTO t VALUES",
+            "INSERT INTO t VALUES (1), ",
+            "INSERT INTO t DEFAULT",
+            "INSERT INTO t VALUES (1) ON CONFLICT DO",
+            "INSERT INTO t VALUES (1) ON CONFLICT (a) UPDATE SET a = 1",
+            "INSERT INTO t VALUES (1) ON DUPLICATE UPDATE a = 1",
+            "INSERT INTO t VALUES (1) RETURNING",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_statement().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let input = "UPDATE dbo.users AS u SET u.name = 'a', active = DEFAULT WHERE id = 1";
+        let update = parse_statement!(Update, input);
+
+        match &update.tables[..] {
+            [TableExpression::Table(table)] => {
+                assert_eq!(table.name, object_name("dbo.users"));
+                assert_eq!(table.alias, Some(Identifier::new("u")));
+            }
+            other => panic!("expected a single table, got {:?}", other),
+        }
+        assert_eq!(update.assignments.len(), 2);
+        assert_eq!(update.assignments[0].column, object_name("u.name"));
+        assert!(matches!(
+            update.assignments[1].value,
+            Expression::Default { .. }
+        ));
+        assert!(update.from.is_none());
+        assert!(update.where_clause.is_some());
+        assert_eq!(update.span.end, input.len());
+    }
+
+    #[test]
+    fn test_update_from_and_multiple_tables() {
+        // t-sql and postgres name the other tables in a FROM list
+        let update = parse_statement!(
+            Update,
+            "UPDATE t SET a = s.a FROM t JOIN s ON t.id = s.id WHERE s.b > 1"
+        );
+        let from = update.from.unwrap();
+        assert!(matches!(from[..], [TableExpression::Join(_)]));
+
+        // mysql joins them onto the updated table instead
+        let update = parse_statement!(Update, "UPDATE t JOIN s ON t.id = s.id SET t.a = s.a");
+        assert!(matches!(update.tables[..], [TableExpression::Join(_)]));
+        assert!(update.where_clause.is_none());
+
+        let update = parse_statement!(Update, "UPDATE t, s SET t.a = s.a WHERE t.id = s.id");
+        assert_eq!(update.tables.len(), 2);
+    }
+
+    #[test]
+    fn test_update_returning_and_output() {
+        let update = parse_statement!(Update, "UPDATE t SET a = 1 WHERE b = 2 RETURNING id");
+        let returning = update.returning.unwrap();
+        assert_eq!(returning.kind, ReturningKind::Returning);
+
+        let update = parse_statement!(
+            Update,
+            "UPDATE t SET a = 1 OUTPUT deleted.a, inserted.a FROM t WHERE b = 2"
+        );
+        let output = update.returning.unwrap();
+        assert_eq!(output.kind, ReturningKind::Output);
+        assert_eq!(output.items.len(), 2);
+        assert!(update.from.is_some());
+    }
+
+    #[test]
+    fn test_update_compound_assignments() {
+        let update = parse_statement!(
+            Update,
+            "UPDATE t SET a += 1, b -= 2, c *= 3, d /= 4, e %= 5, f &= 6, g |= 7, h ^= 8, i = 9"
+        );
+        let operators: Vec<AssignmentOperator> = update
+            .assignments
+            .iter()
+            .map(|assignment| assignment.operator)
+            .collect();
+        assert_eq!(
+            operators,
+            vec![
+                AssignmentOperator::Plus,
+                AssignmentOperator::Minus,
+                AssignmentOperator::Multiply,
+                AssignmentOperator::Divide,
+                AssignmentOperator::Modulo,
+                AssignmentOperator::BitwiseAnd,
+                AssignmentOperator::BitwiseOr,
+                AssignmentOperator::BitwiseXor,
+                AssignmentOperator::Equals,
+            ]
+        );
+
+        let mut parser = Parser::new("UPDATE t SET a < 1");
+        assert!(parser.parse_statement().is_err());
+    }
+
+    #[test]
+    fn test_set_as_a_name() {
+        let update = parse_statement!(Update, "UPDATE settings SET set = set + 1 WHERE set < 5");
+        assert_eq!(update.tables.len(), 1);
+        assert_eq!(update.assignments[0].column, object_name("set"));
+        assert!(update.where_clause.is_some());
+
+        let mut parser = Parser::new("SELECT set FROM t AS set");
+        let stmt = parser.parse_select().unwrap();
+        assert_eq!(first_table(&stmt).alias, Some(Identifier::new("set")));
+    }
+
+    #[test]
+    fn test_invalid_updates() {
+        for input in [
+            "UPDATE",
+            "UPDATE t",
+            "UPDATE t SET",
+            "UPDATE t SET a",
+            "UPDATE t SET a = 1,",
+            "UPDATE t SET a = 1 WHERE",
+            "UPDATE t SET a = 1 FROM",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_statement().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_delete() {
+        let input = "DELETE FROM dbo.logs WHERE created < '2020-01-01' RETURNING id";
+        let delete = parse_statement!(Delete, input);
+
+        match &delete.tables[..] {
+            [TableExpression::Table(table)] => assert_eq!(table.name, object_name("dbo.logs")),
+            other => panic!("expected a single table, got {:?}", other),
+        }
+        assert!(delete.top.is_none());
+        assert!(delete.from.is_none());
+        assert!(delete.using.is_none());
+        assert!(delete.where_clause.is_some());
+        assert_eq!(delete.returning.unwrap().kind, ReturningKind::Returning);
+        assert_eq!(delete.span.end, input.len());
+
+        // FROM is optional in t-sql
+        let delete = parse_statement!(Delete, "DELETE logs");
+        assert_eq!(delete.tables.len(), 1);
+        assert!(delete.where_clause.is_none());
+    }
+
+    #[test]
+    fn test_delete_joins() {
+        // t-sql and mysql name the target and join it in a second FROM list
+        let delete = parse_statement!(
+            Delete,
+            "DELETE o FROM orders o JOIN customers c ON o.cid = c.id"
+        );
+        assert_eq!(delete.tables.len(), 1);
+        assert!(matches!(
+            delete.from.unwrap()[..],
+            [TableExpression::Join(_)]
+        ));
+
+        let delete = parse_statement!(
+            Delete,
+            "DELETE FROM orders o USING customers c, regions r WHERE o.cid = c.id"
+        );
+        match &delete.tables[..] {
+            [TableExpression::Table(table)] => {
+                assert_eq!(table.alias, Some(Identifier::new("o")))
+            }
+            other => panic!("expected a single table, got {:?}", other),
+        }
+        assert_eq!(delete.using.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_top_and_output() {
+        let delete = parse_statement!(
+            Delete,
+            "DELETE TOP (10) FROM logs OUTPUT deleted.* WHERE a = 1"
+        );
+        assert!(matches!(
+            delete.top,
+            Some(Expression::Literal {
+                value: LiteralValue::Number(_),
+                ..
+            })
+        ));
+        // OUTPUT isn't taken as an alias for the table
+        match &delete.tables[..] {
+            [TableExpression::Table(table)] => assert_eq!(table.alias, None),
+            other => panic!("expected a single table, got {:?}", other),
+        }
+        assert_eq!(delete.returning.unwrap().kind, ReturningKind::Output);
+
+        // without parentheses `top` is just a table name
+        let delete = parse_statement!(Delete, "DELETE top WHERE a = 1");
+        assert!(delete.top.is_none());
+    }
+
+    #[test]
+    fn test_invalid_deletes() {
+        for input in [
+            "DELETE",
+            "DELETE FROM",
+            "DELETE TOP (10",
+            "DELETE FROM t WHERE",
+            "DELETE t FROM",
+            "DELETE FROM t USING",
+            "DELETE FROM t RETURNING",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_statement().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let input = "MERGE INTO dbo.customers AS t USING staging.customers s ON t.id = s.id \
+            WHEN MATCHED AND s.deleted = 1 THEN DELETE \
+            WHEN MATCHED THEN UPDATE SET name = s.name, email = s.email \
+            WHEN NOT MATCHED BY TARGET THEN INSERT (id, name) VALUES (s.id, s.name) \
+            WHEN NOT MATCHED BY SOURCE THEN DELETE \
+            OUTPUT $action, inserted.id";
+        let merge = parse_statement!(Merge, input);
+
+        assert_eq!(merge.target.name, object_name("dbo.customers"));
+        assert_eq!(merge.target.alias, Some(Identifier::new("t")));
+        assert!(matches!(merge.source, TableExpression::Table(_)));
+
+        let kinds: Vec<_> = merge.clauses.iter().map(|clause| &clause.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &MergeMatch::Matched,
+                &MergeMatch::Matched,
+                &MergeMatch::NotMatchedByTarget,
+                &MergeMatch::NotMatchedBySource,
+            ]
+        );
+        assert!(merge.clauses[0].condition.is_some());
+        assert_eq!(merge.clauses[0].action, MergeAction::Delete);
+        assert!(
+            matches!(&merge.clauses[1].action, MergeAction::Update(assignments) if assignments.len() == 2)
+        );
+        match &merge.clauses[2].action {
+            MergeAction::Insert { columns, source } => {
+                assert_eq!(columns.len(), 2);
+                assert!(matches!(source, InsertSource::Values(rows) if rows.len() == 1));
+            }
+            other => panic!("expected an insert, got {:?}", other),
+        }
+
+        let output = merge.returning.unwrap();
+        assert_eq!(output.kind, ReturningKind::Output);
+        match &output.items[0] {
+            SelectItem::Expression {
+                expr: Expression::Column { name, .. },
+                ..
+            } => assert_eq!(*name, object_name("$action")),
+            other => panic!("expected $action, got {:?}", other),
+        }
+        assert_eq!(merge.span.end, input.len());
+    }
+
+    #[test]
+    fn test_merge_subquery_source() {
+        let merge = parse_statement!(
+            Merge,
+            "MERGE t USING (SELECT id FROM s) AS src ON t.id = src.id \
+             WHEN NOT MATCHED THEN INSERT DEFAULT VALUES WHEN MATCHED THEN DO NOTHING",
+        );
+        assert!(matches!(merge.source, TableExpression::Derived { .. }));
+        assert_eq!(merge.clauses[0].kind, MergeMatch::NotMatched);
+        assert_eq!(
+            merge.clauses[0].action,
+            MergeAction::Insert {
+                columns: Vec::new(),
+                source: InsertSource::DefaultValues,
+            }
+        );
+        assert_eq!(merge.clauses[1].action, MergeAction::DoNothing);
+    }
+
+    #[test]
+    fn test_merge_words_as_names() {
+        let merge = parse_statement!(
+            Merge,
+            "MERGE INTO merge USING using ON merge.id = using.merge \
+             WHEN MATCHED THEN UPDATE SET using = 1",
+        );
+        assert_eq!(merge.target.name, object_name("merge"));
+        assert_eq!(merge.target.alias, None);
+        assert!(matches!(
+            merge.source,
+            TableExpression::Table(ref table) if table.name == object_name("using")
+        ));
+
+        let mut parser = Parser::new("SELECT merge, using FROM t");
+        assert_eq!(parser.parse_select().unwrap().columns.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_merges() {
+        for input in [
+            "MERGE INTO t",
+            "MERGE INTO t USING s",
+            "MERGE INTO t USING s ON t.id = s.id",
+            "MERGE INTO t USING s ON t.id = s.id WHEN THEN DELETE",
+            "MERGE INTO t USING s ON t.id = s.id WHEN MATCHED DELETE",
+            "MERGE INTO t USING s ON t.id = s.id WHEN NOT MATCHED BY THEN DELETE",
+            "MERGE INTO t USING s ON t.id = s.id WHEN MATCHED THEN UPDATE a = 1",
+            "MERGE INTO t USING s ON t.id = s.id WHEN NOT MATCHED THEN INSERT (a)",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_statement().is_err(), "{}", input);
+        }
+    }
+
+    fn column_constraints(element: &TableElement) -> Vec<&ColumnConstraintKind> {
+        match element {
+            TableElement::Column(column) => column
+                .constraints
+                .iter()
+                .map(|constraint| &constraint.kind)
+                .collect(),
+            other => panic!("expected a column, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_create_table() {
+        let input = "CREATE TEMP TABLE IF NOT EXISTS hr.employees (\
+            id SERIAL PRIMARY KEY, \
+            email VARCHAR(100) NOT NULL UNIQUE CHECK (email LIKE '%@%'), \
+            created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NULL)";
+        let create = parse_statement!(CreateTable, input);
+
+        assert!(create.temporary);
+        assert!(create.if_not_exists);
+        assert_eq!(create.name, object_name("hr.employees"));
+        assert_eq!(create.elements.len(), 3);
+
+        match &create.elements[1] {
+            TableElement::Column(column) => {
+                assert_eq!(column.name, Identifier::new("email"));
+                assert_eq!(column.data_type.name, object_name("VARCHAR"));
+                assert_eq!(
+                    column.data_type.arguments,
+                    vec![TypeArgument::Number("100".to_string())]
+                );
+            }
+            other => panic!("expected a column, got {:?}", other),
+        }
+        assert!(matches!(
+            column_constraints(&create.elements[1])[..],
+            [
+                ColumnConstraintKind::NotNull,
+                ColumnConstraintKind::Unique,
+                ColumnConstraintKind::Check(_)
+            ]
+        ));
+        assert!(matches!(
+            column_constraints(&create.elements[2])[..],
+            [ColumnConstraintKind::Default(_), ColumnConstraintKind::Null]
+        ));
+        assert_eq!(create.span.end, input.len());
+    }
+
+    #[test]
+    fn test_column_constraints() {
+        let create = parse_statement!(
+            CreateTable,
+            "CREATE TABLE t (\
+                a INT IDENTITY(1, 1) CONSTRAINT pk_t PRIMARY KEY, \
+                b BIGINT GENERATED BY DEFAULT AS IDENTITY, \
+                c NUMERIC GENERATED ALWAYS AS (a * 2) STORED, \
+                d INT AUTO_INCREMENT, \
+                e TEXT COLLATE \"C\", \
+                f INT REFERENCES u (id) ON DELETE CASCADE ON UPDATE NO ACTION)",
+        );
+
+        match &create.elements[0] {
+            TableElement::Column(column) => {
+                assert!(matches!(
+                    column.constraints[0].kind,
+                    ColumnConstraintKind::Identity(Some(_))
+                ));
+                assert_eq!(column.constraints[1].name, Some(Identifier::new("pk_t")));
+                assert_eq!(column.constraints[1].kind, ColumnConstraintKind::PrimaryKey);
+            }
+            other => panic!("expected a column, got {:?}", other),
+        }
+        assert_eq!(
+            column_constraints(&create.elements[1]),
+            vec![&ColumnConstraintKind::GeneratedIdentity { always: false }]
+        );
+        assert!(matches!(
+            column_constraints(&create.elements[2])[..],
+            [ColumnConstraintKind::GeneratedAs { stored: true, .. }]
+        ));
+        assert_eq!(
+            column_constraints(&create.elements[3]),
+            vec![&ColumnConstraintKind::AutoIncrement]
+        );
+        assert_eq!(
+            column_constraints(&create.elements[4]),
+            vec![&ColumnConstraintKind::Collate(Identifier::quoted(
+                "C",
+                QuoteStyle::DoubleQuote
+            ))]
+        );
+        match column_constraints(&create.elements[5])[..] {
+            [ColumnConstraintKind::References(references)] => {
+                assert_eq!(references.table, object_name("u"));
+                assert_eq!(references.columns, vec![Identifier::new("id")]);
+                assert_eq!(references.on_delete, Some(ReferentialAction::Cascade));
+                assert_eq!(references.on_update, Some(ReferentialAction::NoAction));
+            }
+            ref other => panic!("expected a reference, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_table_constraints() {
+        let create = parse_statement!(
+            CreateTable,
+            "CREATE TABLE t (a INT, unique INT, PRIMARY KEY (a), UNIQUE (a, unique), \
+             CONSTRAINT fk FOREIGN KEY (a) REFERENCES u (id) ON DELETE SET NULL, \
+             CHECK (a > 0 AND unique > 0))",
+        );
+
+        // `unique` is only a constraint when a column list follows it
+        assert!(matches!(create.elements[1], TableElement::Column(_)));
+
+        let constraints: Vec<_> = create
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                TableElement::Constraint(constraint) => Some(constraint),
+                TableElement::Column(_) => None,
+            })
+            .collect();
+        assert_eq!(constraints.len(), 4);
+        assert_eq!(
+            constraints[0].kind,
+            TableConstraintKind::PrimaryKey(vec![Identifier::new("a")])
+        );
+        assert!(
+            matches!(&constraints[1].kind, TableConstraintKind::Unique(columns) if columns.len() == 2)
+        );
+        assert_eq!(constraints[2].name, Some(Identifier::new("fk")));
+        match &constraints[2].kind {
+            TableConstraintKind::ForeignKey {
+                columns,
+                references,
+            } => {
+                assert_eq!(columns, &vec![Identifier::new("a")]);
+                assert_eq!(references.on_delete, Some(ReferentialAction::SetNull));
+            }
+            other => panic!("expected a foreign key, got {:?}", other),
+        }
+        assert!(matches!(
+            constraints[3].kind,
+            TableConstraintKind::Check(Expression::BinaryOperation {
+                op: Operator::And,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_create_tables() {
+        for input in [
+            "CREATE TABLE",
+            "CREATE VIEW v",
+            "CREATE TABLE t",
+            "CREATE TABLE t ()",
+            "CREATE TABLE t (a)",
+            "CREATE TABLE t (a INT",
+            "CREATE TABLE t (a INT,)",
+            "CREATE TABLE t (a INT NOT)",
+            "CREATE TABLE t (a INT CONSTRAINT c)",
+            "CREATE TABLE t (a INT GENERATED BY DEFAULT AS (1))",
+            "CREATE TABLE t (a INT REFERENCES u ON DELETE)",
+            "CREATE TABLE t (a INT, PRIMARY KEY a)",
+            "CREATE TABLE IF EXISTS t (a INT)",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_statement().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_constraint_as_a_name() {
+        let mut parser = Parser::new("SELECT constraint FROM checks AS constraint");
+        assert_eq!(parser.parse_select().unwrap().columns.len(), 1);
+
+        let create = parse_statement!(
+            CreateTable,
+            "CREATE TABLE t (\"constraint\" TEXT CONSTRAINT nn NOT NULL, CONSTRAINT pk PRIMARY KEY (id))",
+        );
+        assert!(matches!(create.elements[0], TableElement::Column(_)));
+        assert!(matches!(create.elements[1], TableElement::Constraint(_)));
+
+        let alter = parse_statement!(AlterTable, "ALTER TABLE t DROP constraint");
+        assert!(matches!(
+            &alter.actions[0].kind,
+            AlterTableActionKind::DropColumn { name, .. } if *name == "constraint"
+        ));
+    }
+
+    #[test]
+    fn test_parse_statements() {
+        let input =
+            "-- lead\nSELECT 1; -- one\n-- two\nINSERT INTO t VALUES (2);; DELETE FROM t\nSELECT 4";
+        let mut parser = Parser::new(input);
+        let statements = parser.parse_statements().unwrap();
+
+        let terminated: Vec<bool> = statements
+            .iter()
+            .map(|(_, terminated)| *terminated)
+            .collect();
+        assert_eq!(terminated, vec![true, true, false, false]);
+        assert!(matches!(statements[2].0, AST::Delete(_)));
+
+        // a comment on the semicolon's line stays with the statement before it
+        match &statements[0].0 {
+            AST::Query(query) => {
+                let texts: Vec<&str> = query.comments.iter().map(|c| c.text.as_str()).collect();
+                assert_eq!(texts, vec!["-- lead", "-- one"]);
+            }
+            other => panic!("expected a query, got {:?}", other),
+        }
+        match &statements[1].0 {
+            AST::Insert(insert) => assert_eq!(insert.comments[0].text, "-- two"),
+            other => panic!("expected an insert, got {:?}", other),
+        }
+
+        let mut parser = Parser::new(" ; ;");
+        assert!(parser.parse_statements().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_statement_lists() {
+        for input in ["SELECT a FROM t b c", "SELECT 1; FROM t", "SELECT 1;;;)"] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_statements().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_alter_table() {
+        let input = "ALTER TABLE IF EXISTS products \
+            ADD COLUMN description TEXT NOT NULL, \
+            ADD notes TEXT, \
+            ADD CONSTRAINT price_check CHECK (price >= 0), \
+            ADD PRIMARY KEY (id), \
+            DROP COLUMN IF EXISTS outdated CASCADE, \
+            DROP CONSTRAINT old_check, \
+            RENAME COLUMN product_name TO name, \
+            RENAME TO items";
+        let alter = parse_statement!(AlterTable, input);
+
+        assert!(alter.if_exists);
+        assert_eq!(alter.name, object_name("products"));
+        let kinds: Vec<_> = alter.actions.iter().map(|action| &action.kind).collect();
+
+        match kinds[0] {
+            AlterTableActionKind::AddColumn {
+                column_keyword: true,
+                if_not_exists: false,
+                column,
+            } => {
+                assert_eq!(column.name, Identifier::new("description"));
+                assert_eq!(column.constraints.len(), 1);
+            }
+            other => panic!("expected ADD COLUMN, got {:?}", other),
+        }
+        assert!(matches!(
+            kinds[1],
+            AlterTableActionKind::AddColumn {
+                column_keyword: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            kinds[2],
+            AlterTableActionKind::AddConstraint(constraint) if constraint.name == Some(Identifier::new("price_check"))
+        ));
+        assert!(matches!(
+            kinds[3],
+            AlterTableActionKind::AddConstraint(constraint) if constraint.name.is_none()
+        ));
+        assert_eq!(
+            kinds[4],
+            &AlterTableActionKind::DropColumn {
+                if_exists: true,
+                name: Identifier::new("outdated"),
+                behavior: Some(DropBehavior::Cascade),
+            }
+        );
+        assert_eq!(
+            kinds[5],
+            &AlterTableActionKind::DropConstraint {
+                if_exists: false,
+                name: Identifier::new("old_check"),
+                behavior: None,
+            }
+        );
+        assert_eq!(
+            kinds[6],
+            &AlterTableActionKind::RenameColumn {
+                old: Identifier::new("product_name"),
+                new: Identifier::new("name"),
+            }
+        );
+        assert!(
+            matches!(kinds[7], AlterTableActionKind::RenameTable(name) if *name == object_name("items"))
+        );
+        assert_eq!(alter.span.end, input.len());
+    }
+
+    #[test]
+    fn test_alter_column() {
+        let changes = |input: &str| -> Vec<AlterColumnChange> {
+            parse_statement!(AlterTable, input)
+                .actions
+                .into_iter()
+                .map(|action| match action.kind {
+                    AlterTableActionKind::AlterColumn { change, .. } => change,
+                    other => panic!("expected ALTER COLUMN, got {:?}", other),
+                })
+                .collect()
+        };
+
+        let changes = changes(
+            "ALTER TABLE t ALTER COLUMN a SET NOT NULL, ALTER b DROP NOT NULL, \
+             ALTER c SET DEFAULT 0, ALTER d DROP DEFAULT, ALTER e TYPE BIGINT, \
+             ALTER f SET DATA TYPE VARCHAR(10)",
+        );
+        assert_eq!(changes[0], AlterColumnChange::SetNotNull);
+        assert_eq!(changes[1], AlterColumnChange::DropNotNull);
+        assert!(matches!(changes[2], AlterColumnChange::SetDefault(_)));
+        assert_eq!(changes[3], AlterColumnChange::DropDefault);
+        assert!(
+            matches!(&changes[4], AlterColumnChange::SetDataType(data_type) if data_type.name == object_name("BIGINT"))
+        );
+        assert!(
+            matches!(&changes[5], AlterColumnChange::SetDataType(data_type) if data_type.name == object_name("VARCHAR"))
+        );
+    }
+
+    #[test]
+    fn test_drop() {
+        let drop = parse_statement!(Drop, "DROP TABLE IF EXISTS a, dbo.b CASCADE");
+        assert_eq!(drop.object_type, ObjectType::Table);
+        assert!(drop.if_exists);
+        assert_eq!(drop.names.len(), 2);
+        assert_eq!(drop.names[1], object_name("dbo.b"));
+        assert_eq!(drop.behavior, Some(DropBehavior::Cascade));
+
+        let drop = parse_statement!(Drop, "DROP INDEX ix_email ON users");
+        assert_eq!(drop.object_type, ObjectType::Index);
+        assert_eq!(drop.table.unwrap(), object_name("users"));
+
+        assert_eq!(
+            parse_statement!(Drop, "DROP VIEW v").object_type,
+            ObjectType::View
+        );
+        let drop = parse_statement!(Drop, "DROP SCHEMA s RESTRICT");
+        assert_eq!(drop.object_type, ObjectType::Schema);
+        assert_eq!(drop.behavior, Some(DropBehavior::Restrict));
+    }
+
+    #[test]
+    fn test_truncate() {
+        let truncate = parse_statement!(Truncate, "TRUNCATE TABLE a, b RESTART IDENTITY CASCADE");
+        assert_eq!(truncate.tables.len(), 2);
+        assert!(truncate.restart_identity);
+        assert_eq!(truncate.behavior, Some(DropBehavior::Cascade));
+
+        let mut parser = Parser::new("truncate logs");
+        assert!(matches!(
+            parser.parse_statement().unwrap(),
+            AST::Truncate(_)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_ddl() {
+        for input in [
+            "ALTER TABLE t",
+            "ALTER TABLE t ADD",
+            "ALTER TABLE t ADD COLUMN a",
+            "ALTER TABLE t DROP",
+            "ALTER TABLE t ALTER COLUMN a",
+            "ALTER TABLE t ALTER COLUMN a SET",
+            "ALTER TABLE t RENAME COLUMN a",
+            "ALTER TABLE t MODIFY a INT",
+            "ALTER TABLE t ADD COLUMN a INT,",
+            "DROP t",
+            "DROP TABLE",
+            "DROP TABLE IF t",
+            "TRUNCATE",
+            "TRUNCATE TABLE t RESTART",
+        ] {
+            let mut parser = Parser::new(input);
+            assert!(parser.parse_statement().is_err(), "{}", input);
+        }
+    }
 }
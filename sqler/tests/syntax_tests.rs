@@ -0,0 +1,87 @@
+use sqler::ast::TableExpression;
+use sqler::lexer::TokenKind;
+use sqler::parser::Parser;
+use sqler::syntax::{SyntaxElement, SyntaxNodeKind, SyntaxTree};
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let sql = "  select id,\t name -- the name\r\n  FROM users /* all */\n\n";
+        let tree = SyntaxTree::parse(sql);
+
+        assert_eq!(tree.text(), sql);
+    }
+
+    #[test]
+    fn test_trivia_tokens() {
+        let sql = "SELECT /* c */ id";
+        let tree = SyntaxTree::parse(sql);
+        let kinds: Vec<TokenKind> = tree.tokens().iter().map(|t| t.kind.clone()).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Select,
+                TokenKind::Whitespace,
+                TokenKind::BlockComment,
+                TokenKind::Whitespace,
+                TokenKind::Identifier,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parenthesized_nodes() {
+        let sql = "SELECT (a, (b)) FROM t";
+        let tree = SyntaxTree::parse(sql);
+
+        let group = tree
+            .root()
+            .children
+            .iter()
+            .find_map(|child| match child {
+                SyntaxElement::Node(node) => Some(node),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(group.kind, SyntaxNodeKind::Parenthesized);
+        assert_eq!(&sql[group.span.clone()], "(a, (b))");
+        assert!(group
+            .children
+            .iter()
+            .any(|child| matches!(child, SyntaxElement::Node(_))));
+        assert_eq!(tree.text(), sql);
+    }
+
+    #[test]
+    fn test_covering_node() {
+        let sql = "SELECT a FROM (SELECT b FROM (c)) AS d";
+        let tree = SyntaxTree::parse(sql);
+        let select = Parser::from_syntax_tree(&tree).parse_select().unwrap();
+
+        // an AST node maps back to the group holding its text
+        let derived = &select.from.as_ref().unwrap()[0];
+        assert!(matches!(derived, TableExpression::Derived { .. }));
+        let node = tree.covering_node(derived.span().start..derived.span().start + 1);
+        assert_eq!(node.kind, SyntaxNodeKind::Parenthesized);
+        assert_eq!(&sql[node.span.clone()], "(SELECT b FROM (c))");
+
+        let node = tree.covering_node(sql.find('c').unwrap()..sql.find('c').unwrap() + 1);
+        assert_eq!(&sql[node.span.clone()], "(c)");
+
+        // the whole statement is only covered by the root
+        let node = tree.covering_node(select.span.start..select.span.end);
+        assert_eq!(node.kind, SyntaxNodeKind::Root);
+    }
+
+    #[test]
+    fn test_unclosed_parens() {
+        let sql = "SELECT (a, (b FROM t";
+        let tree = SyntaxTree::parse(sql);
+
+        assert_eq!(tree.text(), sql);
+    }
+}